@@ -0,0 +1,1105 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+
+use color_eyre::eyre::Context;
+use fastanvil::Region;
+use fastnbt::{IntArray, Value};
+use tracing::{info, warn};
+
+use crate::blocks::{read_section_blocks, write_section_blocks};
+use crate::fixer::{FixContext, FixerKind, FixerRegistry};
+use crate::level_dat::{read_gzip_nbt, write_gzip_nbt};
+use crate::version::MinecraftVersion;
+use crate::NbtValueExt;
+
+/// Folder in the output world holding one archive per region file, as
+/// `downgrade_archive/<region path>.dat`, and the [`FILES_ARCHIVE`].
+pub const ARCHIVE_DIR: &str = "downgrade_archive";
+
+/// The [`FileArchive`] in the [`ARCHIVE_DIR`].
+pub const FILES_ARCHIVE: &str = "files.dat";
+
+/// Everything a downgrade removed or rewrote in a region, so [`restore_world`] can put it
+/// back.
+///
+/// Each chunk is a compound with its `x` and `z` in the region and
+/// - `Level`: changes to the chunk's own tags,
+/// - `Entities`: changes by `UUIDMost`/`UUIDLeast`, or by `Pos` and `Index` for entities
+///   without a uuid, or the whole entity as `Dropped`. Mounts and passengers have their
+///   own records,
+/// - `TileEntities`: the same by `x`/`y`/`z`,
+/// - `Sections`: the substituted blocks by `Y`, as `Indices` with `Original` and
+///   `Downgraded` values of `id << 4 | data`.
+///
+/// A change is a compound with the `Original` value, the `Downgraded` one, or both.
+#[derive(Debug, Default)]
+pub struct RegionArchive {
+    chunks: Vec<Value>,
+}
+
+impl RegionArchive {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Records how a chunk's `Level` changed, if it did, from its NBT before and after.
+    pub fn record(
+        &mut self,
+        x: usize,
+        z: usize,
+        before: &[u8],
+        after: &[u8],
+    ) -> color_eyre::Result<()> {
+        let before: HashMap<String, Value> = fastnbt::from_bytes(before)?;
+        let after: HashMap<String, Value> = fastnbt::from_bytes(after)?;
+        let (Some(Value::Compound(before)), Some(Value::Compound(after))) =
+            (before.get("Level"), after.get("Level"))
+        else {
+            return Ok(());
+        };
+
+        let mut chunk = HashMap::new();
+
+        let level = diff_compound(before, after, &["Entities", "TileEntities", "Sections"]);
+        if !level.is_empty() {
+            chunk.insert("Level".to_string(), Value::Compound(level));
+        }
+
+        let entities = diff_list(before, after, "Entities");
+        if !entities.is_empty() {
+            chunk.insert("Entities".to_string(), Value::List(entities));
+        }
+
+        let tile_entities = diff_list(before, after, "TileEntities");
+        if !tile_entities.is_empty() {
+            chunk.insert("TileEntities".to_string(), Value::List(tile_entities));
+        }
+
+        let sections = diff_sections(before, after);
+        if !sections.is_empty() {
+            chunk.insert("Sections".to_string(), Value::List(sections));
+        }
+
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        chunk.insert("x".to_string(), Value::Int(x as i32));
+        chunk.insert("z".to_string(), Value::Int(z as i32));
+        self.chunks.push(Value::Compound(chunk));
+
+        Ok(())
+    }
+
+    pub fn write(self, path: &Path) -> color_eyre::Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+
+        write_gzip_nbt(
+            path,
+            &HashMap::from([("Chunks".to_string(), Value::List(self.chunks))]),
+        )
+        .wrap_err_with(|| format!("Failed to write archive to {path:?}."))
+    }
+}
+
+/// Everything a downgrade removed or rewrote in `level.dat`, `level.dat_old` and the player
+/// files, so [`restore_world`] can put it back.
+///
+/// Each file is a compound with its `Path` in the output world, its `OriginalPath` if it
+/// was renamed, like uuid keyed players are for old targets, and the `Changes` to its root
+/// as in a [`RegionArchive`]. Compounds in both versions of a file, like `Data` and
+/// `Player`, are a change with their own `Changes`.
+#[derive(Debug, Default)]
+pub struct FileArchive {
+    files: Vec<Value>,
+}
+
+impl FileArchive {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Records how a file changed, from its NBT before and after. The paths are relative
+    /// to the worlds.
+    pub fn record(
+        &mut self,
+        path: &Path,
+        original_path: &Path,
+        before: &HashMap<String, Value>,
+        after: &HashMap<String, Value>,
+    ) {
+        let changes = diff_nested(before, after);
+        if changes.is_empty() && path == original_path {
+            return;
+        }
+
+        let mut file = HashMap::from([
+            (
+                "Path".to_string(),
+                Value::String(path.to_string_lossy().into_owned()),
+            ),
+            ("Changes".to_string(), Value::Compound(changes)),
+        ]);
+        if path != original_path {
+            file.insert(
+                "OriginalPath".to_string(),
+                Value::String(original_path.to_string_lossy().into_owned()),
+            );
+        }
+
+        self.files.push(Value::Compound(file));
+    }
+
+    pub fn write(self, path: &Path) -> color_eyre::Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+
+        write_gzip_nbt(
+            path,
+            &HashMap::from([("Files".to_string(), Value::List(self.files))]),
+        )
+        .wrap_err_with(|| format!("Failed to write archive to {path:?}."))
+    }
+}
+
+/// The identifying tags of an entity or tile entity.
+type Key = Vec<(&'static str, Value)>;
+
+/// The key tags of entities with a uuid, entities without one and tile entities, in the
+/// order they're tried.
+const KEY_TAGS: &[&[&str]] = &[
+    &["UUIDMost", "UUIDLeast"],
+    &["Pos", "Index"],
+    &["x", "y", "z"],
+];
+
+/// How to get to an entity from a chunk's list, mounts in `Riding` and 1.9 `Passengers`
+/// included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Index(usize),
+    Riding,
+    Passenger(usize),
+}
+
+/// The entities (or tile entities) of a chunk with their keys and where they are. Entities
+/// are keyed by `UUIDMost`/`UUIDLeast`, or by `Pos` and the `Index` among the entities
+/// without a uuid there. Tile entities are keyed by `x`/`y`/`z`. Ones without a key can't
+/// be matched and aren't archived.
+fn keyed<'a>(
+    level: &'a HashMap<String, Value>,
+    list_key: &str,
+) -> Vec<(Key, Vec<Step>, &'a HashMap<String, Value>)> {
+    let list = match level.get(list_key) {
+        Some(Value::List(list)) => &list[..],
+        _ => &[],
+    };
+
+    let mut compounds = vec![];
+    for (index, value) in list.iter().enumerate() {
+        let Value::Compound(compound) = value else {
+            continue;
+        };
+        let path = vec![Step::Index(index)];
+        if list_key == "Entities" {
+            walk_entity(compound, path, &mut compounds);
+        } else {
+            compounds.push((path, compound));
+        }
+    }
+
+    let mut positions = vec![];
+    compounds
+        .into_iter()
+        .filter_map(|(path, compound)| {
+            let key = if list_key == "Entities" {
+                entity_key(compound, &mut positions)
+            } else {
+                tile_entity_key(compound)
+            }?;
+            Some((key, path, compound))
+        })
+        .collect()
+}
+
+/// Adds an entity and then the ones it rides or carries.
+fn walk_entity<'a>(
+    entity: &'a HashMap<String, Value>,
+    path: Vec<Step>,
+    entities: &mut Vec<(Vec<Step>, &'a HashMap<String, Value>)>,
+) {
+    entities.push((path.clone(), entity));
+
+    if let Some(Value::Compound(mount)) = entity.get("Riding") {
+        walk_entity(mount, [&path[..], &[Step::Riding]].concat(), entities);
+    }
+    if let Some(Value::List(passengers)) = entity.get("Passengers") {
+        for (index, passenger) in passengers.iter().enumerate() {
+            if let Value::Compound(passenger) = passenger {
+                let step = Step::Passenger(index);
+                walk_entity(passenger, [&path[..], &[step]].concat(), entities);
+            }
+        }
+    }
+}
+
+/// The entity at `path` in a chunk's list.
+fn entity_at<'a>(list: &'a mut [Value], path: &[Step]) -> Option<&'a mut HashMap<String, Value>> {
+    let (Step::Index(index), path) = path.split_first()? else {
+        return None;
+    };
+
+    let mut entity = list.get_mut(*index)?.as_compound()?;
+    for step in path {
+        entity = match step {
+            Step::Index(_) => return None,
+            Step::Riding => entity.get_mut("Riding")?.as_compound()?,
+            Step::Passenger(index) => entity
+                .get_mut("Passengers")?
+                .as_list()?
+                .get_mut(*index)?
+                .as_compound()?,
+        };
+    }
+
+    Some(entity)
+}
+
+/// `positions` are those of the entities without a uuid so far.
+fn entity_key(entity: &HashMap<String, Value>, positions: &mut Vec<Value>) -> Option<Key> {
+    if let (Some(most), Some(least)) = (entity.get("UUIDMost"), entity.get("UUIDLeast")) {
+        return Some(vec![
+            ("UUIDMost", most.clone()),
+            ("UUIDLeast", least.clone()),
+        ]);
+    }
+
+    let position = entity.get("Pos")?.clone();
+    let index = positions.iter().filter(|other| **other == position).count();
+    positions.push(position.clone());
+
+    Some(vec![("Pos", position), ("Index", Value::Int(index as i32))])
+}
+
+fn tile_entity_key(tile_entity: &HashMap<String, Value>) -> Option<Key> {
+    Some(vec![
+        ("x", tile_entity.get("x")?.clone()),
+        ("y", tile_entity.get("y")?.clone()),
+        ("z", tile_entity.get("z")?.clone()),
+    ])
+}
+
+/// The key an archived entity or tile entity was recorded with.
+fn record_key(record: &HashMap<String, Value>) -> Option<Key> {
+    KEY_TAGS.iter().find_map(|tags| {
+        tags.iter()
+            .map(|tag| Some((*tag, record.get(*tag)?.clone())))
+            .collect()
+    })
+}
+
+fn change(original: Option<&Value>, downgraded: Option<&Value>) -> Value {
+    let mut change = HashMap::new();
+    if let Some(original) = original {
+        change.insert("Original".to_string(), original.clone());
+    }
+    if let Some(downgraded) = downgraded {
+        change.insert("Downgraded".to_string(), downgraded.clone());
+    }
+
+    Value::Compound(change)
+}
+
+/// Every removed, rewritten or added tag, apart from the `skip`ped ones.
+fn diff_compound(
+    before: &HashMap<String, Value>,
+    after: &HashMap<String, Value>,
+    skip: &[&str],
+) -> HashMap<String, Value> {
+    let mut changes = HashMap::new();
+
+    for key in before.keys().chain(after.keys()) {
+        if skip.contains(&key.as_str()) || changes.contains_key(key) {
+            continue;
+        }
+
+        let (original, downgraded) = (before.get(key), after.get(key));
+        if original != downgraded {
+            changes.insert(key.clone(), change(original, downgraded));
+        }
+    }
+
+    changes
+}
+
+/// Like [`diff_compound`], but compounds in both are diffed as well, so a file's `Data`
+/// only conflicts where the old version changed the same tags.
+fn diff_nested(
+    before: &HashMap<String, Value>,
+    after: &HashMap<String, Value>,
+) -> HashMap<String, Value> {
+    let mut changes = diff_compound(before, after, &[]);
+
+    for (key, change) in changes.iter_mut() {
+        if let (Some(Value::Compound(before)), Some(Value::Compound(after))) =
+            (before.get(key), after.get(key))
+        {
+            *change = Value::Compound(HashMap::from([(
+                "Changes".to_string(),
+                Value::Compound(diff_nested(before, after)),
+            )]));
+        }
+    }
+
+    changes
+}
+
+fn compounds<'a>(level: &'a HashMap<String, Value>, key: &str) -> Vec<&'a HashMap<String, Value>> {
+    match level.get(key) {
+        Some(Value::List(list)) => list
+            .iter()
+            .filter_map(|value| match value {
+                Value::Compound(compound) => Some(compound),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Changes to the entities (or tile entities) in `before`, matched up by their [`keyed`]
+/// keys. Mounts and passengers get records of their own, apart from those of a dropped
+/// entity, which are dropped along with it.
+fn diff_list(
+    before: &HashMap<String, Value>,
+    after: &HashMap<String, Value>,
+    list_key: &str,
+) -> Vec<Value> {
+    let after = keyed(after, list_key);
+
+    let mut records = vec![];
+    let mut dropped: Vec<Vec<Step>> = vec![];
+    for (key, path, before) in keyed(before, list_key) {
+        if dropped.iter().any(|parent| path.starts_with(parent)) {
+            continue;
+        }
+
+        let mut record: HashMap<String, Value> = key
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+
+        match after.iter().find(|(other, _, _)| *other == key) {
+            Some((_, _, after)) => {
+                let changes = diff_compound(before, after, &["Riding", "Passengers"]);
+                if changes.is_empty() {
+                    continue;
+                }
+                record.insert("Changes".to_string(), Value::Compound(changes));
+            }
+            None => {
+                record.insert("Dropped".to_string(), Value::Compound(before.clone()));
+                dropped.push(path);
+            }
+        }
+
+        records.push(Value::Compound(record));
+    }
+
+    records
+}
+
+fn sections_by_y(level: &HashMap<String, Value>) -> Vec<(i8, Vec<(u16, u8)>)> {
+    compounds(level, "Sections")
+        .into_iter()
+        .filter_map(|section| {
+            let Some(Value::Byte(y)) = section.get("Y") else {
+                return None;
+            };
+
+            Some((*y, read_section_blocks(section)?))
+        })
+        .collect()
+}
+
+fn pack_block((id, data): (u16, u8)) -> i32 {
+    (id as i32) << 4 | (data & 0xf) as i32
+}
+
+fn unpack_block(block: i32) -> (u16, u8) {
+    ((block >> 4) as u16, (block & 0xf) as u8)
+}
+
+fn diff_sections(before: &HashMap<String, Value>, after: &HashMap<String, Value>) -> Vec<Value> {
+    let after = sections_by_y(after);
+
+    let mut records = vec![];
+    for (y, before_blocks) in sections_by_y(before) {
+        let Some((_, after_blocks)) = after.iter().find(|(other, _)| *other == y) else {
+            continue;
+        };
+
+        let (mut indices, mut originals, mut downgraded) = (vec![], vec![], vec![]);
+        for (index, (before, after)) in before_blocks.iter().zip(after_blocks).enumerate() {
+            if before != after {
+                indices.push(index as i32);
+                originals.push(pack_block(*before));
+                downgraded.push(pack_block(*after));
+            }
+        }
+
+        if indices.is_empty() {
+            continue;
+        }
+
+        records.push(Value::Compound(HashMap::from([
+            ("Y".to_string(), Value::Byte(y)),
+            (
+                "Indices".to_string(),
+                Value::IntArray(IntArray::new(indices)),
+            ),
+            (
+                "Original".to_string(),
+                Value::IntArray(IntArray::new(originals)),
+            ),
+            (
+                "Downgraded".to_string(),
+                Value::IntArray(IntArray::new(downgraded)),
+            ),
+        ])));
+    }
+
+    records
+}
+
+/// How much of an archive could be put back.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RestoreStats {
+    pub chunks: usize,
+    /// `level.dat`, `level.dat_old` and player files.
+    pub files: usize,
+    /// Values put back.
+    pub restored: usize,
+    /// Values that were changed since the downgrade, which are left as they are now.
+    pub conflicts: usize,
+}
+
+/// Puts everything in a world's [`ARCHIVE_DIR`] back into its regions and files, in place.
+/// Values the old version changed since are kept, and running it again changes nothing.
+pub fn restore_world(world_path: &Path) -> color_eyre::Result<RestoreStats> {
+    let archive_path = world_path.join(ARCHIVE_DIR);
+    if !archive_path.exists() {
+        warn!("No {ARCHIVE_DIR} in {world_path:?}, nothing to restore.");
+        return Ok(RestoreStats::default());
+    }
+
+    let mut stats = RestoreStats::default();
+    let mut upgrader = Upgrader::new();
+    let files_archive_path = archive_path.join(FILES_ARCHIVE);
+    if files_archive_path.exists() {
+        restore_files(world_path, &files_archive_path, &mut upgrader, &mut stats)
+            .wrap_err_with(|| format!("Failed to restore the files in {files_archive_path:?}."))?;
+    }
+
+    let mut dirs = vec![archive_path.clone()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path == files_archive_path {
+                continue;
+            }
+
+            let relative_path = path.strip_prefix(&archive_path)?.with_extension("mca");
+            let region_path = world_path.join(&relative_path);
+            if !region_path.exists() {
+                warn!("No region {relative_path:?} for archive {path:?}, skipping.");
+                continue;
+            }
+
+            info!("Restoring region {relative_path:?}");
+            restore_region(&region_path, &path, &mut upgrader, &mut stats)
+                .wrap_err_with(|| format!("Failed to restore region {relative_path:?}."))?;
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Puts back a [`FileArchive`], moving renamed files back to where they were.
+fn restore_files(
+    world_path: &Path,
+    archive_path: &Path,
+    upgrader: &mut Upgrader,
+    stats: &mut RestoreStats,
+) -> color_eyre::Result<()> {
+    let archive = read_gzip_nbt(archive_path)?;
+    let Some(files) = archive.get("Files").and_then(list) else {
+        return Ok(());
+    };
+
+    for record in files.iter().filter_map(compound) {
+        let Some(Value::String(relative_path)) = record.get("Path") else {
+            continue;
+        };
+        let original_relative_path = match record.get("OriginalPath") {
+            Some(Value::String(original_relative_path)) => original_relative_path,
+            _ => relative_path,
+        };
+        let (path, original_path) = (
+            world_path.join(relative_path),
+            world_path.join(original_relative_path),
+        );
+
+        // after an earlier restore it's already back where it was
+        let current_path = if path.exists() {
+            &path
+        } else if original_path.exists() {
+            &original_path
+        } else {
+            warn!("{relative_path:?} is gone, skipping its archive.");
+            continue;
+        };
+
+        // player files are the player, level.dat has it in its Data
+        let is_player = ["players", "playerdata"]
+            .iter()
+            .any(|dir| Path::new(original_relative_path).starts_with(dir));
+        let kind = is_player.then_some(FixerKind::Player);
+
+        let mut root = read_gzip_nbt(current_path)?;
+        if let Some(changes) = record.get("Changes").and_then(compound) {
+            restore_compound(&mut root, changes, kind, upgrader, stats);
+        }
+
+        fs::create_dir_all(original_path.parent().unwrap())?;
+        write_gzip_nbt(&original_path, &root)?;
+        if *current_path != original_path {
+            fs::remove_file(current_path)?;
+        }
+
+        info!("Restored {original_relative_path:?}");
+        stats.files += 1;
+    }
+
+    Ok(())
+}
+
+fn restore_region(
+    region_path: &Path,
+    archive_path: &Path,
+    upgrader: &mut Upgrader,
+    stats: &mut RestoreStats,
+) -> color_eyre::Result<()> {
+    let mut archive = read_gzip_nbt(archive_path)?;
+    let Some(chunks) = archive.get_mut("Chunks").and_then(NbtValueExt::as_list) else {
+        return Ok(());
+    };
+
+    let mut region =
+        Region::from_stream(File::options().read(true).write(true).open(region_path)?)?;
+
+    for record in chunks {
+        let Some(record) = record.as_compound() else {
+            continue;
+        };
+        let (Some(Value::Int(x)), Some(Value::Int(z))) = (record.get("x"), record.get("z")) else {
+            continue;
+        };
+        let (x, z) = (*x as usize, *z as usize);
+
+        let Some(data) = region.read_chunk(x, z)? else {
+            warn!("Chunk {x}, {z} is gone, skipping its archive.");
+            continue;
+        };
+
+        let mut chunk: HashMap<String, Value> = fastnbt::from_bytes(&data)?;
+        let Some(level) = chunk.get_mut("Level").and_then(NbtValueExt::as_compound) else {
+            warn!("Chunk {x}, {z} has no Level, skipping its archive.");
+            continue;
+        };
+
+        restore_chunk(level, record, upgrader, stats);
+        stats.chunks += 1;
+
+        region.write_chunk(x, z, &fastnbt::to_bytes(&chunk)?)?;
+    }
+
+    Ok(())
+}
+
+fn restore_chunk(
+    level: &mut HashMap<String, Value>,
+    record: &HashMap<String, Value>,
+    upgrader: &mut Upgrader,
+    stats: &mut RestoreStats,
+) {
+    if let Some(changes) = record.get("Level").and_then(compound) {
+        restore_compound(level, changes, Some(FixerKind::Level), upgrader, stats);
+    }
+
+    for (list_key, kind) in [
+        ("Entities", FixerKind::Entity),
+        ("TileEntities", FixerKind::TileEntity),
+    ] {
+        let Some(records) = record.get(list_key).and_then(list) else {
+            continue;
+        };
+
+        for record in records.iter().filter_map(compound) {
+            let Some(key) = record_key(record) else {
+                continue;
+            };
+
+            let path = keyed(level, list_key)
+                .into_iter()
+                .find_map(|(other, path, _)| (other == key).then_some(path));
+            let list = level
+                .entry(list_key.to_string())
+                .or_insert_with(|| Value::List(vec![]));
+            let Some(list) = list.as_list() else {
+                continue;
+            };
+
+            match (
+                path.and_then(|path| entity_at(list, &path)),
+                record.get("Changes").and_then(compound),
+                record.get("Dropped"),
+            ) {
+                (Some(current), Some(changes), _) => {
+                    restore_compound(current, changes, Some(kind), upgrader, stats)
+                }
+                // dropped mounts and passengers come back on their own
+                (None, _, Some(dropped)) => {
+                    list.push(dropped.clone());
+                    stats.restored += 1;
+                }
+                // still there (or removed on the old version), leave it be
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(records) = record.get("Sections").and_then(list) {
+        restore_sections(level, records, stats);
+    }
+}
+
+/// Runs downgraded values through the upgrade fixers, since the newer version rewrites
+/// them in its own format when it loads the world, like numeric item ids becoming names.
+struct Upgrader {
+    registry: FixerRegistry,
+    ctx: FixContext,
+}
+
+impl Upgrader {
+    fn new() -> Self {
+        Self {
+            registry: FixerRegistry::with_upgrades(),
+            ctx: FixContext::new(MinecraftVersion::V1_8),
+        }
+    }
+
+    /// `compound` with the downgraded values of `changes` put back and upgraded again.
+    fn upgrade(
+        &mut self,
+        kind: FixerKind,
+        compound: &HashMap<String, Value>,
+        changes: &HashMap<String, Value>,
+    ) -> Option<HashMap<String, Value>> {
+        let mut upgraded: HashMap<String, Value> = compound
+            .iter()
+            .filter(|(key, _)| !["Entities", "TileEntities", "Sections"].contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        for (key, change) in changes {
+            let Value::Compound(change) = change else {
+                continue;
+            };
+            if change.contains_key("Changes") {
+                continue;
+            }
+
+            match change.get("Downgraded") {
+                Some(downgraded) => upgraded.insert(key.clone(), downgraded.clone()),
+                None => upgraded.remove(key),
+            };
+        }
+
+        self.registry
+            .apply(kind, &mut upgraded, &mut self.ctx)
+            .ok()?;
+
+        Some(upgraded)
+    }
+}
+
+/// Puts back the values of `changes` that are still as downgraded, or as the upgrade
+/// fixers of a `kind` of compound would have left them.
+fn restore_compound(
+    compound: &mut HashMap<String, Value>,
+    changes: &HashMap<String, Value>,
+    kind: Option<FixerKind>,
+    upgrader: &mut Upgrader,
+    stats: &mut RestoreStats,
+) {
+    let upgraded = kind.and_then(|kind| upgrader.upgrade(kind, compound, changes));
+
+    for (key, change) in changes {
+        let Value::Compound(change) = change else {
+            continue;
+        };
+        if let Some(Value::Compound(changes)) = change.get("Changes") {
+            let kind = match (kind, key.as_str()) {
+                (None, "Data") => Some(FixerKind::LevelData),
+                (Some(FixerKind::LevelData), "Player") => Some(FixerKind::Player),
+                _ => None,
+            };
+            match compound.get_mut(key).and_then(NbtValueExt::as_compound) {
+                Some(current) => restore_compound(current, changes, kind, upgrader, stats),
+                None => stats.conflicts += 1,
+            }
+            continue;
+        }
+
+        let (original, downgraded) = (change.get("Original"), change.get("Downgraded"));
+        let current = compound.get(key);
+
+        if current == original {
+            continue;
+        }
+
+        let upgraded = upgraded.as_ref().map(|upgraded| upgraded.get(key));
+        if current.is_none() || current == downgraded || upgraded == Some(current) {
+            match original {
+                Some(original) => compound.insert(key.clone(), original.clone()),
+                None => compound.remove(key),
+            };
+            stats.restored += 1;
+        } else {
+            stats.conflicts += 1;
+        }
+    }
+}
+
+fn restore_sections(
+    level: &mut HashMap<String, Value>,
+    records: &[Value],
+    stats: &mut RestoreStats,
+) {
+    let Some(sections) = level.get_mut("Sections").and_then(NbtValueExt::as_list) else {
+        return;
+    };
+
+    for record in records.iter().filter_map(compound) {
+        let (
+            Some(Value::Byte(y)),
+            Some(Value::IntArray(indices)),
+            Some(Value::IntArray(originals)),
+            Some(Value::IntArray(downgraded)),
+        ) = (
+            record.get("Y"),
+            record.get("Indices"),
+            record.get("Original"),
+            record.get("Downgraded"),
+        )
+        else {
+            continue;
+        };
+
+        let Some(section) = sections
+            .iter_mut()
+            .filter_map(|s| s.as_compound())
+            .find(|section| matches!(section.get("Y"), Some(Value::Byte(other)) if other == y))
+        else {
+            continue;
+        };
+        let Some(mut blocks) = read_section_blocks(section) else {
+            continue;
+        };
+
+        for ((index, original), downgraded) in
+            indices.iter().zip(originals.iter()).zip(downgraded.iter())
+        {
+            let Some(block) = blocks.get_mut(*index as usize) else {
+                continue;
+            };
+
+            if *block == unpack_block(*original) {
+                continue;
+            }
+
+            if *block == unpack_block(*downgraded) {
+                *block = unpack_block(*original);
+                stats.restored += 1;
+            } else {
+                stats.conflicts += 1;
+            }
+        }
+
+        write_section_blocks(section, &blocks);
+    }
+}
+
+fn compound(value: &Value) -> Option<&HashMap<String, Value>> {
+    match value {
+        Value::Compound(compound) => Some(compound),
+        _ => None,
+    }
+}
+
+fn list(value: &Value) -> Option<&Vec<Value>> {
+    match value {
+        Value::List(list) => Some(list),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::region::{self, ChunkErrorPolicy};
+    use crate::selection::{BoundingBox, Outside, RegionSelection};
+
+    fn nbt<const N: usize>(tags: [(&str, Value); N]) -> HashMap<String, Value> {
+        tags.into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    fn open(path: &Path) -> File {
+        File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .unwrap()
+    }
+
+    #[test]
+    fn restore_puts_back_a_downgraded_chunk() {
+        let world_path: PathBuf =
+            std::env::temp_dir().join(format!("pv1_dfu-restore-{}", std::process::id()));
+        fs::create_dir_all(world_path.join("region")).unwrap();
+        let input_path = world_path.join("input.mca");
+        let region_path = world_path.join("region/r.0.0.mca");
+
+        let zombie = nbt([
+            ("id", Value::String("Zombie".into())),
+            ("UUIDMost", Value::Long(1)),
+            ("UUIDLeast", Value::Long(2)),
+            ("Health", Value::Short(20)),
+            (
+                "Attributes",
+                Value::List(vec![Value::Compound(nbt([
+                    ("Name", Value::String("generic.maxHealth".into())),
+                    ("Base", Value::Double(20.0)),
+                ]))]),
+            ),
+        ]);
+        let chest = nbt([
+            ("id", Value::String("Chest".into())),
+            ("x", Value::Int(1)),
+            ("y", Value::Int(64)),
+            ("z", Value::Int(2)),
+            ("CustomName", Value::String("Loot".into())),
+            ("Items", Value::List(vec![])),
+        ]);
+        let chunk = nbt([(
+            "Level",
+            Value::Compound(nbt([
+                ("xPos", Value::Int(0)),
+                ("zPos", Value::Int(0)),
+                ("InhabitedTime", Value::Long(1200)),
+                ("Entities", Value::List(vec![Value::Compound(zombie)])),
+                ("TileEntities", Value::List(vec![Value::Compound(chest)])),
+                ("Sections", Value::List(vec![])),
+            ])),
+        )]);
+
+        let mut input = Region::new(open(&input_path)).unwrap();
+        input
+            .write_chunk(0, 0, &fastnbt::to_bytes(&chunk).unwrap())
+            .unwrap();
+        drop(input);
+
+        let archive_path = world_path.join(ARCHIVE_DIR).join("region/r.0.0.dat");
+        region::convert_region_file(
+            &input_path,
+            &region_path,
+            RegionSelection {
+                bbox: BoundingBox::REGION,
+                outside: Outside::Skip,
+            },
+            &ChunkErrorPolicy::Drop,
+            Some(&archive_path),
+            &FixerRegistry::with_defaults(),
+            &mut FixContext::new(MinecraftVersion::V1_4_7),
+        )
+        .unwrap();
+
+        let read_chunk = || -> HashMap<String, Value> {
+            let data = Region::from_stream(open(&region_path))
+                .unwrap()
+                .read_chunk(0, 0)
+                .unwrap()
+                .unwrap();
+            fastnbt::from_bytes(&data).unwrap()
+        };
+        let mut downgraded = read_chunk();
+        let level = downgraded.get_mut("Level").unwrap().as_compound().unwrap();
+        assert!(!level.contains_key("InhabitedTime"));
+        let first = |level: &mut HashMap<String, Value>, key: &str| {
+            let list = level.get_mut(key).unwrap().as_list().unwrap();
+            list[0].as_compound().unwrap().clone()
+        };
+        assert!(!first(level, "Entities").contains_key("Attributes"));
+        assert!(!first(level, "TileEntities").contains_key("CustomName"));
+
+        let stats = restore_world(&world_path).unwrap();
+        let restored = read_chunk();
+        fs::remove_dir_all(&world_path).unwrap();
+
+        assert_eq!(restored, chunk);
+        assert_eq!((stats.chunks, stats.conflicts), (1, 0));
+    }
+
+    fn slime(slot: Option<i8>) -> Value {
+        let mut item = nbt([
+            ("id", Value::String("minecraft:slime".into())),
+            ("Count", Value::Byte(1)),
+            ("Damage", Value::Short(0)),
+        ]);
+        if let Some(slot) = slot {
+            item.insert("Slot".to_string(), Value::Byte(slot));
+        }
+
+        Value::Compound(item)
+    }
+
+    #[test]
+    fn restore_recognises_values_upgraded_since() {
+        let world_path: PathBuf =
+            std::env::temp_dir().join(format!("pv1_dfu-restore-upgraded-{}", std::process::id()));
+        fs::create_dir_all(world_path.join("region")).unwrap();
+        let input_path = world_path.join("input.mca");
+        let upgraded_path = world_path.join("upgraded.mca");
+        let region_path = world_path.join("region/r.0.0.mca");
+
+        let pos = Value::List(vec![
+            Value::Double(1.5),
+            Value::Double(64.0),
+            Value::Double(2.5),
+        ]);
+        // a spider jockey, the spider has no uuid
+        let spider = nbt([
+            ("id", Value::String("Spider".into())),
+            ("Pos", pos.clone()),
+            ("Equipment", Value::List(vec![slime(None)])),
+        ]);
+        let skeleton = nbt([
+            ("id", Value::String("Skeleton".into())),
+            ("UUIDMost", Value::Long(1)),
+            ("UUIDLeast", Value::Long(2)),
+            ("Pos", pos.clone()),
+            ("Equipment", Value::List(vec![slime(None)])),
+            ("Riding", Value::Compound(spider)),
+        ]);
+        let item = nbt([
+            ("id", Value::String("Item".into())),
+            ("Pos", pos),
+            ("Item", slime(None)),
+        ]);
+        let chest = nbt([
+            ("id", Value::String("Chest".into())),
+            ("x", Value::Int(1)),
+            ("y", Value::Int(64)),
+            ("z", Value::Int(2)),
+            ("Items", Value::List(vec![slime(Some(0))])),
+        ]);
+        let sign = nbt([
+            ("id", Value::String("Sign".into())),
+            ("x", Value::Int(2)),
+            ("y", Value::Int(64)),
+            ("z", Value::Int(2)),
+            (
+                "Text1",
+                Value::String(r#"{"text":"Hi","color":"red"}"#.into()),
+            ),
+            ("Text2", Value::String(r#""""#.into())),
+            ("Text3", Value::String(r#""""#.into())),
+            ("Text4", Value::String(r#""""#.into())),
+        ]);
+        let chunk = nbt([(
+            "Level",
+            Value::Compound(nbt([
+                ("xPos", Value::Int(0)),
+                ("zPos", Value::Int(0)),
+                (
+                    "Entities",
+                    Value::List(vec![Value::Compound(skeleton), Value::Compound(item)]),
+                ),
+                (
+                    "TileEntities",
+                    Value::List(vec![Value::Compound(chest), Value::Compound(sign)]),
+                ),
+                ("Sections", Value::List(vec![])),
+            ])),
+        )]);
+
+        let mut input = Region::new(open(&input_path)).unwrap();
+        input
+            .write_chunk(0, 0, &fastnbt::to_bytes(&chunk).unwrap())
+            .unwrap();
+        drop(input);
+
+        let selection = RegionSelection {
+            bbox: BoundingBox::REGION,
+            outside: Outside::Skip,
+        };
+        let archive_path = world_path.join(ARCHIVE_DIR).join("region/r.0.0.dat");
+        region::convert_region_file(
+            &input_path,
+            &region_path,
+            selection,
+            &ChunkErrorPolicy::Drop,
+            Some(&archive_path),
+            &FixerRegistry::with_defaults(),
+            &mut FixContext::new(MinecraftVersion::V1_7_10),
+        )
+        .unwrap();
+
+        // like loading the downgraded world on 1.8 again
+        region::convert_region_file(
+            &region_path,
+            &upgraded_path,
+            selection,
+            &ChunkErrorPolicy::Drop,
+            None,
+            &FixerRegistry::with_upgrades(),
+            &mut FixContext::new(MinecraftVersion::V1_8),
+        )
+        .unwrap();
+        fs::copy(&upgraded_path, &region_path).unwrap();
+
+        let stats = restore_world(&world_path).unwrap();
+        let data = Region::from_stream(open(&region_path))
+            .unwrap()
+            .read_chunk(0, 0)
+            .unwrap()
+            .unwrap();
+        let restored: HashMap<String, Value> = fastnbt::from_bytes(&data).unwrap();
+        fs::remove_dir_all(&world_path).unwrap();
+
+        assert_eq!(restored, chunk);
+        assert_eq!(stats.conflicts, 0);
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+
+use fastnbt::{ByteArray, Value};
+
+use crate::ids;
+use crate::nibble::{pack_nibbles, NibbleIter};
+use crate::version::MinecraftVersion;
+use crate::NbtValueExt;
+
+/// A block (id and data) that doesn't exist before `since`, and what to put in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSubstitution {
+    pub since: MinecraftVersion,
+    pub id: u16,
+    /// The data bits that have to equal `data` for the substitution to apply.
+    pub data_mask: u8,
+    pub data: u8,
+    pub replacement_id: u16,
+    pub replacement_data: u8,
+    /// Data bits carried over from the original block, like the upper half bit of slabs.
+    pub keep_data: u8,
+}
+
+/// Any data value of `id`.
+const fn block(
+    since: MinecraftVersion,
+    id: u16,
+    replacement_id: u16,
+    replacement_data: u8,
+    keep_data: u8,
+) -> BlockSubstitution {
+    variant(since, id, 0, 0, replacement_id, replacement_data, keep_data)
+}
+
+/// Only the data values of `id` where `data_mask` bits equal `data`.
+const fn variant(
+    since: MinecraftVersion,
+    id: u16,
+    data_mask: u8,
+    data: u8,
+    replacement_id: u16,
+    replacement_data: u8,
+    keep_data: u8,
+) -> BlockSubstitution {
+    BlockSubstitution {
+        since,
+        id,
+        data_mask,
+        data,
+        replacement_id,
+        replacement_data,
+        keep_data,
+    }
+}
+
+/// Substitutions for blocks that old clients can't render or crash on, newest first. The
+/// replacements are picked to look and behave about the same, or are air when nothing fits.
+pub const DEFAULT_SUBSTITUTIONS: &[BlockSubstitution] = {
+    use MinecraftVersion::*;
+
+    &[
+        // 1.9
+        block(V1_9_4, 198, 50, 5, 0),    // end rod -> torch
+        block(V1_9_4, 199, 0, 0, 0),     // chorus plant
+        block(V1_9_4, 200, 0, 0, 0),     // chorus flower
+        block(V1_9_4, 201, 35, 10, 0),   // purpur block -> purple wool
+        block(V1_9_4, 202, 35, 10, 0),   // purpur pillar -> purple wool
+        block(V1_9_4, 203, 156, 0, 0xf), // purpur stairs -> quartz stairs
+        block(V1_9_4, 204, 43, 7, 0),    // double purpur slab -> double quartz slab
+        block(V1_9_4, 205, 44, 7, 0x8),  // purpur slab -> quartz slab
+        block(V1_9_4, 206, 121, 0, 0),   // end stone bricks -> end stone
+        block(V1_9_4, 207, 141, 0, 0x3), // beetroots -> carrots
+        block(V1_9_4, 208, 2, 0, 0),     // grass path -> grass
+        block(V1_9_4, 209, 0, 0, 0),     // end gateway
+        block(V1_9_4, 210, 137, 0, 0),   // repeating command block -> command block
+        block(V1_9_4, 211, 137, 0, 0),   // chain command block -> command block
+        block(V1_9_4, 212, 79, 0, 0),    // frosted ice -> ice
+        block(V1_9_4, 255, 0, 0, 0),     // structure block
+        // 1.8
+        variant(V1_8, 1, 0xf, 1, 1, 0, 0),   // granite
+        variant(V1_8, 1, 0xf, 2, 1, 0, 0),   // polished granite
+        variant(V1_8, 1, 0xf, 3, 1, 0, 0),   // diorite
+        variant(V1_8, 1, 0xf, 4, 1, 0, 0),   // polished diorite
+        variant(V1_8, 1, 0xf, 5, 1, 0, 0),   // andesite
+        variant(V1_8, 1, 0xf, 6, 1, 0, 0),   // polished andesite
+        variant(V1_8, 19, 0xf, 1, 19, 0, 0), // wet sponge
+        block(V1_8, 165, 35, 5, 0),          // slime block -> lime wool
+        block(V1_8, 166, 20, 0, 0),          // barrier -> glass
+        block(V1_8, 167, 96, 0, 0xf),        // iron trapdoor -> trapdoor
+        block(V1_8, 168, 98, 0, 0),          // prismarine -> stone bricks
+        block(V1_8, 169, 89, 0, 0),          // sea lantern -> glowstone
+        block(V1_8, 176, 0, 0, 0),           // standing banner
+        block(V1_8, 177, 0, 0, 0),           // wall banner
+        block(V1_8, 178, 151, 0, 0xf),       // inverted daylight detector
+        block(V1_8, 179, 24, 0, 0x3),        // red sandstone -> sandstone
+        block(V1_8, 180, 128, 0, 0xf),       // red sandstone stairs -> sandstone stairs
+        block(V1_8, 181, 43, 1, 0x8),        // double red sandstone slab -> sandstone
+        block(V1_8, 182, 44, 1, 0x8),        // red sandstone slab -> sandstone slab
+        block(V1_8, 183, 107, 0, 0xf),       // spruce fence gate
+        block(V1_8, 184, 107, 0, 0xf),       // birch fence gate
+        block(V1_8, 185, 107, 0, 0xf),       // jungle fence gate
+        block(V1_8, 186, 107, 0, 0xf),       // dark oak fence gate
+        block(V1_8, 187, 107, 0, 0xf),       // acacia fence gate
+        block(V1_8, 188, 85, 0, 0),          // spruce fence
+        block(V1_8, 189, 85, 0, 0),          // birch fence
+        block(V1_8, 190, 85, 0, 0),          // jungle fence
+        block(V1_8, 191, 85, 0, 0),          // dark oak fence
+        block(V1_8, 192, 85, 0, 0),          // acacia fence
+        block(V1_8, 193, 64, 0, 0xf),        // spruce door
+        block(V1_8, 194, 64, 0, 0xf),        // birch door
+        block(V1_8, 195, 64, 0, 0xf),        // jungle door
+        block(V1_8, 196, 64, 0, 0xf),        // acacia door
+        block(V1_8, 197, 64, 0, 0xf),        // dark oak door
+        // 1.7
+        variant(V1_7, 3, 0xf, 1, 3, 0, 0),       // coarse dirt
+        variant(V1_7, 3, 0xf, 2, 3, 0, 0),       // podzol
+        variant(V1_7, 5, 0xf, 4, 5, 0, 0),       // acacia planks
+        variant(V1_7, 5, 0xf, 5, 5, 0, 0),       // dark oak planks
+        variant(V1_7, 6, 0x7, 4, 6, 0, 0x8),     // acacia sapling
+        variant(V1_7, 6, 0x7, 5, 6, 0, 0x8),     // dark oak sapling
+        variant(V1_7, 12, 0xf, 1, 12, 0, 0),     // red sand
+        variant(V1_7, 38, 0xf, 1, 38, 0, 0),     // blue orchid
+        variant(V1_7, 38, 0xf, 2, 38, 0, 0),     // allium
+        variant(V1_7, 38, 0xf, 3, 38, 0, 0),     // azure bluet
+        variant(V1_7, 38, 0xf, 4, 38, 0, 0),     // red tulip
+        variant(V1_7, 38, 0xf, 5, 38, 0, 0),     // orange tulip
+        variant(V1_7, 38, 0xf, 6, 38, 0, 0),     // white tulip
+        variant(V1_7, 38, 0xf, 7, 38, 0, 0),     // pink tulip
+        variant(V1_7, 38, 0xf, 8, 38, 0, 0),     // oxeye daisy
+        variant(V1_7, 97, 0xf, 3, 97, 2, 0),     // infested mossy stone bricks
+        variant(V1_7, 97, 0xf, 4, 97, 2, 0),     // infested cracked stone bricks
+        variant(V1_7, 97, 0xf, 5, 97, 2, 0),     // infested chiseled stone bricks
+        variant(V1_7, 125, 0x7, 4, 125, 0, 0x8), // double acacia slab
+        variant(V1_7, 125, 0x7, 5, 125, 0, 0x8), // double dark oak slab
+        variant(V1_7, 126, 0x7, 4, 126, 0, 0x8), // acacia slab
+        variant(V1_7, 126, 0x7, 5, 126, 0, 0x8), // dark oak slab
+        block(V1_7, 95, 20, 0, 0),               // stained glass (was the locked chest before)
+        block(V1_7, 160, 102, 0, 0),             // stained glass pane
+        block(V1_7, 161, 18, 0, 0xc),            // acacia & dark oak leaves -> oak leaves
+        block(V1_7, 162, 17, 0, 0xc),            // acacia & dark oak log -> oak log
+        block(V1_7, 163, 53, 0, 0xf),            // acacia stairs -> oak stairs
+        block(V1_7, 164, 53, 0, 0xf),            // dark oak stairs -> oak stairs
+        block(V1_7, 174, 79, 0, 0),              // packed ice -> ice
+        variant(V1_7, 175, 0x8, 0x8, 0, 0, 0),   // double plant (upper half)
+        block(V1_7, 175, 31, 1, 0),              // double plant -> tall grass
+        // 1.6
+        block(V1_6, 159, 35, 0, 0xf), // stained clay -> wool of the same colour
+        block(V1_6, 170, 35, 4, 0),   // hay bale -> yellow wool
+        block(V1_6, 171, 0, 0, 0),    // carpet
+        block(V1_6, 172, 45, 0, 0),   // hardened clay -> bricks
+        block(V1_6, 173, 35, 15, 0),  // block of coal -> black wool
+        // 1.5
+        variant(V1_5, 43, 0xf, 7, 43, 0, 0), // double quartz slab
+        variant(V1_5, 44, 0x7, 7, 44, 0, 0x8), // quartz slab
+        block(V1_5, 146, 54, 0, 0xf),        // trapped chest -> chest
+        block(V1_5, 147, 70, 0, 0),          // light weighted pressure plate
+        block(V1_5, 148, 70, 0, 0),          // heavy weighted pressure plate
+        block(V1_5, 149, 93, 0, 0x3),        // redstone comparator -> repeater
+        block(V1_5, 150, 93, 0, 0x3),        // powered redstone comparator -> repeater
+        block(V1_5, 151, 126, 0, 0),         // daylight detector -> oak slab
+        block(V1_5, 152, 35, 14, 0),         // block of redstone -> red wool
+        block(V1_5, 153, 87, 0, 0),          // nether quartz ore -> netherrack
+        block(V1_5, 154, 54, 0, 0),          // hopper -> chest
+        block(V1_5, 155, 80, 0, 0),          // block of quartz -> snow block
+        block(V1_5, 156, 109, 0, 0xf),       // quartz stairs -> stone brick stairs
+        block(V1_5, 157, 27, 0, 0xf),        // activator rail -> powered rail
+        block(V1_5, 158, 23, 0, 0xf),        // dropper -> dispenser
+        // 1.4.6
+        variant(V1_4_6, 43, 0xf, 6, 43, 0, 0), // double nether brick slab
+        variant(V1_4_6, 44, 0x7, 6, 44, 0, 0x8), // nether brick slab
+    ]
+};
+
+/// Every `id:data` resolved to what it becomes for one target version.
+#[derive(Clone)]
+pub struct BlockMap {
+    table: Vec<(u16, u8)>,
+}
+
+impl BlockMap {
+    const IDS: usize = 4096;
+
+    pub fn new(target: MinecraftVersion, substitutions: &[BlockSubstitution]) -> Self {
+        let substitutions: Vec<_> = substitutions
+            .iter()
+            .filter(|substitution| target < substitution.since)
+            .collect();
+
+        let mut table = Vec::with_capacity(Self::IDS * 16);
+        for id in 0..Self::IDS as u16 {
+            for data in 0..16 {
+                let (mut id, mut data) = (id, data);
+
+                // replacements can be newer than the target too (inverted daylight
+                // detector -> daylight detector -> oak slab), so resolve until nothing
+                // matches, bounded in case a substitution file loops
+                for _ in 0..substitutions.len() {
+                    let Some(substitution) = substitutions.iter().find(|substitution| {
+                        substitution.id == id && data & substitution.data_mask == substitution.data
+                    }) else {
+                        break;
+                    };
+
+                    id = substitution.replacement_id;
+                    data = substitution.replacement_data | (data & substitution.keep_data);
+                }
+
+                // a block the target doesn't have that nothing substitutes
+                if !ids::exists_in(id as i16, target) {
+                    (id, data) = (0, 0);
+                }
+
+                table.push((id, data));
+            }
+        }
+
+        Self { table }
+    }
+
+    pub fn get(&self, id: u16, data: u8) -> (u16, u8) {
+        self.table
+            .get((id as usize) * 16 + (data & 0xf) as usize)
+            .copied()
+            .unwrap_or((id, data))
+    }
+
+    /// Rewrites `Blocks`, `Data` and `Add` of every section in a chunk's `Level`, and the
+    /// block ids in its `TileTicks`.
+    pub fn downgrade_level(&self, level: &mut HashMap<String, Value>) {
+        if let Some(sections) = level.get_mut("Sections").and_then(|s| s.as_list()) {
+            for section in sections {
+                if let Some(section) = section.as_compound() {
+                    self.downgrade_section(section);
+                }
+            }
+        }
+
+        if let Some(tile_ticks) = level.get_mut("TileTicks").and_then(|t| t.as_list()) {
+            for tile_tick in tile_ticks {
+                let Some(tile_tick) = tile_tick.as_compound() else {
+                    continue;
+                };
+
+                if let Some(Value::Int(id)) = tile_tick.get_mut("i") {
+                    *id = self.get(*id as u16, 0).0 as i32;
+                }
+            }
+        }
+    }
+
+    /// Rewrites the blocks carried by falling blocks and endermen.
+    pub fn downgrade_entity(&self, entity: &mut HashMap<String, Value>) {
+        // falling blocks have an int `TileID` next to the byte `Tile` since 1.6
+        let (id_keys, data_key): (&[&str], _) = match entity.get("id").and_then(Value::as_str) {
+            Some("FallingSand") => (&["TileID", "Tile"], "Data"),
+            Some("Enderman") => (&["carried"], "carriedData"),
+            _ => return,
+        };
+
+        let (Some(id), data) = (
+            id_keys.iter().find_map(|key| match entity.get(*key)? {
+                Value::Byte(id) => Some(*id as u8 as i64),
+                id => id.as_i64(),
+            }),
+            entity.get(data_key).and_then(Value::as_i64).unwrap_or(0),
+        ) else {
+            return;
+        };
+
+        let (new_id, new_data) = self.get(id as u16, data as u8);
+
+        for id_key in id_keys {
+            if let Some(value) = entity.get_mut(*id_key) {
+                set_int(value, new_id as i64);
+            }
+        }
+        if let Some(value) = entity.get_mut(data_key) {
+            set_int(value, new_data as i64);
+        }
+    }
+
+    fn downgrade_section(&self, section: &mut HashMap<String, Value>) {
+        let Some(blocks) = read_section_blocks(section) else {
+            return;
+        };
+
+        let blocks: Vec<_> = blocks
+            .into_iter()
+            .map(|(id, data)| self.get(id, data))
+            .collect();
+
+        write_section_blocks(section, &blocks);
+    }
+}
+
+/// The `id:data` of every block in a section, decoded from `Blocks`, `Data` and `Add`.
+pub fn read_section_blocks(section: &HashMap<String, Value>) -> Option<Vec<(u16, u8)>> {
+    let (Some(Value::ByteArray(blocks)), Some(Value::ByteArray(data))) =
+        (section.get("Blocks"), section.get("Data"))
+    else {
+        return None;
+    };
+
+    let data: Vec<u8> = NibbleIter::from_iter(data.iter().map(|b| *b as u8)).collect();
+    let add: Vec<u8> = match section.get("Add") {
+        Some(Value::ByteArray(add)) => {
+            NibbleIter::from_iter(add.iter().map(|b| *b as u8)).collect()
+        }
+        _ => vec![],
+    };
+
+    Some(
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| {
+                let id = (*block as u8 as u16) | ((*add.get(index).unwrap_or(&0) as u16) << 8);
+                (id, *data.get(index).unwrap_or(&0))
+            })
+            .collect(),
+    )
+}
+
+/// The inverse of [`read_section_blocks`], `Add` is only written when needed.
+pub fn write_section_blocks(section: &mut HashMap<String, Value>, blocks: &[(u16, u8)]) {
+    section.insert(
+        "Blocks".into(),
+        Value::ByteArray(ByteArray::new(
+            blocks.iter().map(|(id, _)| *id as u8 as i8).collect(),
+        )),
+    );
+    section.insert(
+        "Data".into(),
+        Value::ByteArray(nibble_array(blocks.iter().map(|(_, data)| *data).collect())),
+    );
+
+    if blocks.iter().any(|(id, _)| *id > 0xff) {
+        section.insert(
+            "Add".into(),
+            Value::ByteArray(nibble_array(
+                blocks.iter().map(|(id, _)| (*id >> 8) as u8).collect(),
+            )),
+        );
+    } else {
+        section.remove("Add");
+    }
+}
+
+/// Sets an integer tag without changing its type.
+fn set_int(value: &mut Value, new: i64) {
+    match value {
+        Value::Byte(value) => *value = new as i8,
+        Value::Short(value) => *value = new as i16,
+        Value::Int(value) => *value = new as i32,
+        Value::Long(value) => *value = new,
+        _ => {}
+    }
+}
+
+fn nibble_array(nibbles: Vec<u8>) -> ByteArray {
+    ByteArray::new(
+        pack_nibbles(nibbles)
+            .into_iter()
+            .map(|byte| byte as i8)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MinecraftVersion::*;
+
+    #[test]
+    fn block_map_resolves_chains() {
+        let map = BlockMap::new(V1_4_7, DEFAULT_SUBSTITUTIONS);
+        // inverted daylight detector -> daylight detector -> oak slab
+        assert_eq!(map.get(178, 5), (126, 0));
+        // an upper quartz slab stays an upper slab
+        assert_eq!(map.get(44, 0xf), (44, 0x8));
+        assert_eq!(map.get(1, 0), (1, 0));
+
+        let map = BlockMap::new(V1_7_10, DEFAULT_SUBSTITUTIONS);
+        assert_eq!(map.get(178, 5), (151, 5));
+        assert_eq!(map.get(1, 3), (1, 0));
+
+        let map = BlockMap::new(V1_8, DEFAULT_SUBSTITUTIONS);
+        assert_eq!(map.get(1, 3), (1, 3));
+
+        // a block the target lacks that nothing substitutes becomes air
+        let map = BlockMap::new(V1_7_10, &[]);
+        assert_eq!(map.get(165, 0), (0, 0));
+    }
+
+    #[test]
+    fn block_map_stops_on_loops() {
+        let substitutions = [block(V1_9_4, 1, 2, 0, 0), block(V1_9_4, 2, 1, 0, 0)];
+
+        let map = BlockMap::new(V1_8, &substitutions);
+        assert!(matches!(map.get(1, 0), (1, 0) | (2, 0)));
+    }
+
+    #[test]
+    fn section_blocks_round_trip() {
+        let blocks: Vec<(u16, u8)> = (0..4096).map(|i| (i as u16, (i % 16) as u8)).collect();
+
+        let mut section = HashMap::new();
+        write_section_blocks(&mut section, &blocks);
+        assert!(section.contains_key("Add"));
+        assert_eq!(read_section_blocks(&section), Some(blocks));
+
+        // Add is left out when every id fits in a byte
+        write_section_blocks(&mut section, &[(1, 2); 4096]);
+        assert!(!section.contains_key("Add"));
+        assert_eq!(read_section_blocks(&section), Some(vec![(1, 2); 4096]));
+    }
+}
@@ -0,0 +1,349 @@
+use serde_json::{Map, Value};
+
+/// The en_US text of the translation keys likely to end up on a sign or in a book.
+const EN_US: &[(&str, &str)] = &[
+    (
+        "chat.type.achievement",
+        "%s has just earned the achievement %s",
+    ),
+    ("chat.type.admin", "[%s: %s]"),
+    ("chat.type.announcement", "[%s] %s"),
+    ("chat.type.emote", "* %s %s"),
+    ("chat.type.text", "<%s> %s"),
+    ("container.chest", "Chest"),
+    ("container.chestDouble", "Large Chest"),
+    ("container.crafting", "Crafting"),
+    ("container.dispenser", "Dispenser"),
+    ("container.dropper", "Dropper"),
+    ("container.enderchest", "Ender Chest"),
+    ("container.furnace", "Furnace"),
+    ("container.hopper", "Item Hopper"),
+    ("container.inventory", "Inventory"),
+    ("death.attack.drown", "%1$s drowned"),
+    ("death.attack.fall", "%1$s hit the ground too hard"),
+    ("death.attack.generic", "%1$s died"),
+    ("death.attack.inFire", "%1$s went up in flames"),
+    ("death.attack.lava", "%1$s tried to swim in lava"),
+    ("death.attack.mob", "%1$s was slain by %2$s"),
+    ("death.attack.outOfWorld", "%1$s fell out of the world"),
+    ("death.attack.player", "%1$s was slain by %2$s"),
+    ("gameMode.adventure", "Adventure Mode"),
+    ("gameMode.creative", "Creative Mode"),
+    ("gameMode.hardcore", "Hardcore Mode!"),
+    ("gameMode.spectator", "Spectator Mode"),
+    ("gameMode.survival", "Survival Mode"),
+    ("item.apple.name", "Apple"),
+    ("item.bread.name", "Bread"),
+    ("item.diamond.name", "Diamond"),
+    ("item.emerald.name", "Emerald"),
+    ("item.ingotGold.name", "Gold Ingot"),
+    ("item.ingotIron.name", "Iron Ingot"),
+    ("item.sign.name", "Sign"),
+    ("multiplayer.player.joined", "%s joined the game"),
+    ("multiplayer.player.left", "%s left the game"),
+    ("options.difficulty.easy", "Easy"),
+    ("options.difficulty.hard", "Hard"),
+    ("options.difficulty.normal", "Normal"),
+    ("options.difficulty.peaceful", "Peaceful"),
+    ("tile.chest.name", "Chest"),
+    ("tile.dirt.default.name", "Dirt"),
+    ("tile.grass.name", "Grass Block"),
+    ("tile.stone.stone.name", "Stone"),
+    ("tile.stonebrick.name", "Cobblestone"),
+    ("tile.wood.oak.name", "Oak Wood Planks"),
+];
+
+/// `color`s by their formatting code.
+const COLORS: &[(&str, char)] = &[
+    ("black", '0'),
+    ("dark_blue", '1'),
+    ("dark_green", '2'),
+    ("dark_aqua", '3'),
+    ("dark_red", '4'),
+    ("dark_purple", '5'),
+    ("gold", '6'),
+    ("gray", '7'),
+    ("dark_gray", '8'),
+    ("blue", '9'),
+    ("green", 'a'),
+    ("aqua", 'b'),
+    ("red", 'c'),
+    ("light_purple", 'd'),
+    ("yellow", 'e'),
+    ("white", 'f'),
+];
+
+/// Formatting flags by their formatting code, in the order they're written.
+const FORMATS: &[(&str, char)] = &[
+    ("obfuscated", 'k'),
+    ("bold", 'l'),
+    ("strikethrough", 'm'),
+    ("underlined", 'n'),
+    ("italic", 'o'),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    color: Option<char>,
+    /// Set bits are the [`FORMATS`] in effect.
+    formats: u8,
+}
+
+impl Style {
+    /// This style with what a component sets, which its `extra`s inherit.
+    fn with(mut self, component: &Map<String, Value>) -> Self {
+        if let Some(color) = component.get("color").and_then(Value::as_str) {
+            self.color = COLORS
+                .iter()
+                .find(|(name, _)| *name == color)
+                .map(|(_, code)| *code);
+        }
+
+        for (bit, (name, _)) in FORMATS.iter().enumerate() {
+            let set = match component.get(*name) {
+                Some(Value::Bool(set)) => *set,
+                Some(Value::String(set)) => set == "true",
+                _ => continue,
+            };
+
+            if set {
+                self.formats |= 1 << bit;
+            } else {
+                self.formats &= !(1 << bit);
+            }
+        }
+
+        self
+    }
+
+    /// The codes switching from `previous` to this style. Colours reset the formatting, so
+    /// it's written again after one, unless the style only adds formatting.
+    fn codes_from(&self, previous: Style) -> String {
+        let (mut codes, formats) = if self.color == previous.color
+            && self.formats & previous.formats == previous.formats
+        {
+            (String::new(), self.formats & !previous.formats)
+        } else {
+            (format!("§{}", self.color.unwrap_or('r')), self.formats)
+        };
+
+        for (bit, (_, code)) in FORMATS.iter().enumerate() {
+            if formats & (1 << bit) != 0 {
+                codes.push('§');
+                codes.push(*code);
+            }
+        }
+
+        codes
+    }
+}
+
+/// Flattens a JSON chat component to the `§` formatted text older versions use.
+pub fn to_legacy(component: &Value) -> String {
+    let mut legacy = Legacy::default();
+    legacy.component(component, Style::default());
+    legacy.text
+}
+
+/// Removes the `§` formatting codes from legacy text.
+pub fn strip_codes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// The first `max` characters of legacy text, without a `§` cut off from its code.
+pub fn truncate(text: &str, max: usize) -> String {
+    let mut truncated: String = text.chars().take(max).collect();
+    if truncated.ends_with('§') {
+        truncated.pop();
+    }
+
+    truncated
+}
+
+#[derive(Debug, Default)]
+struct Legacy {
+    text: String,
+    style: Style,
+}
+
+impl Legacy {
+    fn component(&mut self, component: &Value, parent: Style) {
+        match component {
+            Value::Null => {}
+            Value::Bool(_) | Value::Number(_) => self.push(&component.to_string(), parent),
+            Value::String(text) => self.push(text, parent),
+            // the first component is the parent of the rest
+            Value::Array(components) => {
+                let Some((first, rest)) = components.split_first() else {
+                    return;
+                };
+
+                self.component(first, parent);
+                let style = match first {
+                    Value::Object(first) => parent.with(first),
+                    _ => parent,
+                };
+                for component in rest {
+                    self.component(component, style);
+                }
+            }
+            Value::Object(component) => {
+                let style = parent.with(component);
+
+                if let Some(text) = component.get("text") {
+                    self.component(text, style);
+                } else if let Some(key) = component.get("translate").and_then(Value::as_str) {
+                    let with = match component.get("with") {
+                        Some(Value::Array(with)) => &with[..],
+                        _ => &[],
+                    };
+                    self.translate(key, with, style);
+                } else if let Some(score) = component.get("score") {
+                    // only set when a server resolved it
+                    self.component(score.get("value").unwrap_or(&Value::Null), style);
+                } else if let Some(selector) = component.get("selector") {
+                    self.component(selector, style);
+                }
+
+                if let Some(Value::Array(extra)) = component.get("extra") {
+                    for component in extra {
+                        self.component(component, style);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fills in a translation's `%s`, `%d` and `%<n>$s` with the `with` components. Unknown
+    /// keys are written as they are, like the game does.
+    fn translate(&mut self, key: &str, with: &[Value], style: Style) {
+        let Some((_, format)) = EN_US.iter().find(|(k, _)| *k == key) else {
+            self.push(key, style);
+            return;
+        };
+
+        let mut next_arg = 0;
+        let mut rest = *format;
+        while let Some(start) = rest.find('%') {
+            self.push(&rest[..start], style);
+            rest = &rest[start + 1..];
+
+            if let Some(after) = rest.strip_prefix('%') {
+                self.push("%", style);
+                rest = after;
+                continue;
+            }
+
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let index = match rest[digits..].strip_prefix('$') {
+                Some(after) => {
+                    let index = rest[..digits]
+                        .parse::<usize>()
+                        .unwrap_or(1)
+                        .saturating_sub(1);
+                    rest = after;
+                    index
+                }
+                None => {
+                    next_arg += 1;
+                    next_arg - 1
+                }
+            };
+            // the conversion, s or d
+            rest = rest.get(1..).unwrap_or("");
+
+            self.component(with.get(index).unwrap_or(&Value::Null), style);
+        }
+        self.push(rest, style);
+    }
+
+    fn push(&mut self, text: &str, style: Style) {
+        if text.is_empty() {
+            return;
+        }
+
+        if style != self.style {
+            self.text.push_str(&style.codes_from(self.style));
+            self.style = style;
+        }
+        self.text.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn extras_inherit_styles() {
+        let component = json!({
+            "text": "a",
+            "color": "red",
+            "extra": [{"text": "b", "bold": true}, {"text": "c"}, {"text": "d", "color": "blue"}],
+        });
+        // the colour is written again to end the bold
+        assert_eq!(to_legacy(&component), "§ca§lb§cc§9d");
+
+        // an array's first component styles the rest
+        let component = json!([{"text": "x", "color": "gold", "italic": true}, "y", {"text": "z"}]);
+        assert_eq!(to_legacy(&component), "§6§oxyz");
+    }
+
+    #[test]
+    fn resets_without_a_color() {
+        let component = json!({"text": "", "extra": [{"text": "a", "bold": true}, "b"]});
+        assert_eq!(to_legacy(&component), "§la§rb");
+
+        let component = json!([{"text": "x", "color": "gold"}, {"text": "y", "color": "reset"}]);
+        assert_eq!(to_legacy(&component), "§6x§ry");
+
+        let component = json!({"text": "a", "bold": true, "extra": [{"text": "b", "bold": false, "underlined": true}]});
+        assert_eq!(to_legacy(&component), "§la§r§nb");
+    }
+
+    #[test]
+    fn translations_fill_in_arguments() {
+        let component = json!({"translate": "chat.type.text", "with": ["Steve", "hi"]});
+        assert_eq!(to_legacy(&component), "<Steve> hi");
+
+        let component = json!({
+            "translate": "death.attack.mob",
+            "with": ["Steve", {"text": "Zombie", "color": "red"}],
+        });
+        assert_eq!(to_legacy(&component), "Steve was slain by §cZombie");
+
+        // arguments keep the translation's style, missing ones are left out
+        let component =
+            json!({"translate": "death.attack.player", "color": "gold", "with": ["Steve"]});
+        assert_eq!(to_legacy(&component), "§6Steve was slain by ");
+
+        let component = json!({"translate": "some.mod.key", "with": ["x"]});
+        assert_eq!(to_legacy(&component), "some.mod.key");
+    }
+
+    #[test]
+    fn truncates_and_strips_codes() {
+        assert_eq!(strip_codes("§cRed §lbold§r."), "Red bold.");
+        assert_eq!(strip_codes("dangling§"), "dangling");
+
+        assert_eq!(truncate("short", 15), "short");
+        assert_eq!(truncate("§cfifteen chars!!", 15), "§cfifteen chars");
+        // a § as the 15th character would lose its code
+        assert_eq!(truncate("fourteen chars§cX", 15), "fourteen chars");
+        assert_eq!(truncate("éééééééééééééééé", 15).chars().count(), 15);
+    }
+}
@@ -1,51 +1,20 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::time::{Duration, SystemTime};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
 
-use chrono::format::Item;
-use chrono::serde::ts_seconds;
-use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use color_eyre::eyre::{bail, Context, OptionExt};
 use fastanvil::Region;
-use fastnbt::error::Result;
-use fastnbt::{ByteArray, Value};
-use serde::Deserialize;
-
-#[derive(Debug, Clone, Copy)]
-#[repr(u8)]
-enum MinecraftVersion {
-    V1_4_5,
-    V1_4_6,
-    V1_4_7,
-    V1_5,
-    V1_5_1,
-    V1_5_2,
-    V1_6,
-    V1_6_1,
-    V1_6_2,
-    V1_6_3,
-    V1_6_4,
-    V1_7,
-    V1_7_1,
-    V1_7_2,
-    V1_7_3,
-    V1_7_4,
-    V1_7_5,
-    V1_7_6,
-    V1_7_7,
-    V1_7_8,
-    V1_7_10,
-    V1_8,
-    V1_8_1,
-    V1_8_2,
-    V1_8_3,
-    V1_8_4,
-    V1_8_5,
-    V1_8_6,
-    V1_8_7,
-    V1_8_8,
-    V1_8_9,
-    V1_9_4,
-}
+use fastnbt::Value;
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::blocks::read_section_blocks;
+use crate::ids;
+use crate::version::MinecraftVersion;
+use crate::world::{self, Dimension};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -53,267 +22,966 @@ struct ChunkContainer {
     level: HashMap<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "PascalCase")]
-struct Chunk {
-    data_version: i32,
+/// The range of versions that could have saved a chunk, judged by what's in it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkFingerprint {
+    /// The region file, relative to the world when fingerprinting a whole world.
+    pub region: String,
+    pub x: i32,
+    pub z: i32,
+    pub min: MinecraftVersion,
+    pub max: MinecraftVersion,
+    /// What raised `min`, `None` if nothing did.
+    pub min_evidence: Option<String>,
+    /// What lowered `max`, `None` if nothing did.
+    pub max_evidence: Option<String>,
+}
+
+/// Which chunks a regeneration plan picks, by their fingerprint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VersionFilter {
+    /// Chunks saved by this version or newer, like `min >= 1.7`.
+    pub min_version: Option<MinecraftVersion>,
+    /// Chunks saved by this version or older, like `max <= 1.6.4`.
+    pub max_version: Option<MinecraftVersion>,
 }
 
-#[derive(Hash, PartialEq, Eq)]
-struct BlockIdData(i32, u8);
+impl VersionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.min_version.is_none() && self.max_version.is_none()
+    }
 
-impl std::fmt::Debug for BlockIdData {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.0, self.1)
+    pub fn matches(&self, fingerprint: &ChunkFingerprint) -> bool {
+        self.min_version.is_none_or(|min| fingerprint.min >= min)
+            && self.max_version.is_none_or(|max| fingerprint.max <= max)
     }
 }
 
-fn main() {
-    let path = std::env::args().nth(1).unwrap();
-    dbg!(&path);
-    let file = File::open(&path).unwrap();
+/// The chunks of one region file to regenerate.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionPlan {
+    pub region: String,
+    /// Chunk coordinates, not region relative ones.
+    pub chunks: Vec<[i32; 2]>,
+}
 
-    let mut region = Region::from_stream(file).unwrap();
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum FingerprintFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
 
-    for chunkd in region.iter().flatten() {
-        let mut min_version = MinecraftVersion::V1_4_5 as u8;
-        let mut max_version = MinecraftVersion::V1_9_4 as u8;
+/// Fingerprints every chunk of a world folder, or of a single region file.
+pub fn fingerprint_path(path: &Path) -> color_eyre::Result<Vec<ChunkFingerprint>> {
+    if path.is_file() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        return fingerprint_region(path, &name);
+    }
 
-        // std::fs::write("chunk.dat", &chunkd.data).unwrap();
+    let mut fingerprints = vec![];
+    for relative_path in world::region_files(path)? {
+        fingerprints.extend(fingerprint_region(
+            &path.join(&relative_path),
+            &relative_path.to_string_lossy(),
+        )?);
+    }
 
-        let chunk: ChunkContainer = fastnbt::from_bytes(&chunkd.data).unwrap();
-        let chunk = chunk.level;
+    Ok(fingerprints)
+}
 
-        let x = chunk["xPos"].as_i64().unwrap();
-        let z = chunk["zPos"].as_i64().unwrap();
-        // println!("{{\"x\":{x},\"z\":{z}}},");
-        // continue;
+fn fingerprint_region(path: &Path, name: &str) -> color_eyre::Result<Vec<ChunkFingerprint>> {
+    let file = File::open(path).wrap_err_with(|| format!("Failed to open region {path:?}."))?;
+    let mut region =
+        Region::from_stream(file).wrap_err_with(|| format!("Failed to read region {path:?}."))?;
+
+    let mut fingerprints = vec![];
+    for chunk in region.iter() {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                warn!("Skipping an unreadable chunk in {path:?}: {err}");
+                continue;
+            }
+        };
 
-        let mut level_key_versions: HashMap<
-            &str,
-            (Option<MinecraftVersion>, Option<MinecraftVersion>),
-        > = HashMap::new();
-        level_key_versions.insert(
-            "V",
-            (Some(MinecraftVersion::V1_7), Some(MinecraftVersion::V1_8_9)),
-        );
-        level_key_versions.insert("LightPopulated", (Some(MinecraftVersion::V1_7), None));
-        level_key_versions.insert("InhabitedTime", (Some(MinecraftVersion::V1_6), None));
-        // TileTicks
-
-        for key in chunk.keys() {
-            if let Some((min, max)) = level_key_versions.get(key.as_str()) {
-                if let Some(min) = min {
-                    min_version = min_version.max(*min as _);
+        match fingerprint_chunk(&chunk.data) {
+            Ok((x, z, range)) => {
+                if range.min > range.max {
+                    warn!(
+                        "Chunk {x},{z} in {path:?} has contradicting evidence, at least {} ({}) \
+                         but at most {} ({}).",
+                        range.min,
+                        range.min_evidence.as_deref().unwrap_or_default(),
+                        range.max,
+                        range.max_evidence.as_deref().unwrap_or_default(),
+                    );
                 }
 
-                if let Some(max) = max {
-                    max_version = max_version.min(*max as _);
-                }
+                fingerprints.push(ChunkFingerprint {
+                    region: name.to_string(),
+                    x,
+                    z,
+                    min: range.min,
+                    max: range.max,
+                    min_evidence: range.min_evidence,
+                    max_evidence: range.max_evidence,
+                });
             }
+            Err(err) => warn!("Skipping chunk {},{} in {path:?}: {err}", chunk.x, chunk.z),
         }
+    }
 
-        let mut tile_tick_key_versions: HashMap<
-            &str,
-            (Option<MinecraftVersion>, Option<MinecraftVersion>),
-        > = HashMap::new();
-        tile_tick_key_versions.insert("BlockLight", (Some(MinecraftVersion::V1_4_6), None));
+    Ok(fingerprints)
+}
 
-        if let Some(tile_ticks) = chunk.get("TileTicks").and_then(|v| v.as_list()) {
-            match tile_ticks[0].as_compound().unwrap()["i"] {
-                Value::Int(_) => max_version = max_version.min(MinecraftVersion::V1_7_10 as _),
-                Value::String(_) => min_version = min_version.max(MinecraftVersion::V1_8 as _),
-                _ => {}
-            }
+/// Versions a key or id tells apart: present only from `min` on, or only up to `max`.
+type Evidence = (
+    &'static str,
+    Option<MinecraftVersion>,
+    Option<MinecraftVersion>,
+);
+
+const LEVEL_KEYS: &[Evidence] = {
+    use MinecraftVersion::*;
+
+    &[
+        ("InhabitedTime", Some(V1_6), None),
+        ("V", Some(V1_7), Some(V1_8_9)),
+        ("LightPopulated", Some(V1_7), None),
+    ]
+};
+
+const ENTITY_IDS: &[Evidence] = {
+    use MinecraftVersion::*;
+
+    &[
+        ("MinecartTNT", Some(V1_5), None),
+        ("MinecartHopper", Some(V1_5), None),
+        ("MinecartSpawner", Some(V1_5), None),
+        ("EntityHorse", Some(V1_6), None),
+        ("LeashKnot", Some(V1_6), None),
+        ("MinecartCommandBlock", Some(V1_7), None),
+        ("ArmorStand", Some(V1_8), None),
+        ("Guardian", Some(V1_8), None),
+        ("Endermite", Some(V1_8), None),
+        ("Rabbit", Some(V1_8), None),
+        ("Shulker", Some(V1_9_4), None),
+        ("ShulkerBullet", Some(V1_9_4), None),
+        ("DragonFireball", Some(V1_9_4), None),
+        ("AreaEffectCloud", Some(V1_9_4), None),
+        ("SpectralArrow", Some(V1_9_4), None),
+    ]
+};
+
+/// Keys on any entity, they're specific enough not to need the entity id.
+const ENTITY_KEYS: &[Evidence] = {
+    use MinecraftVersion::*;
+
+    &[
+        ("HealF", Some(V1_6), None),
+        ("Attributes", Some(V1_6), None),
+        ("AbsorptionAmount", Some(V1_6), None),
+        ("Thrower", Some(V1_7), None),
+        ("life", Some(V1_7), None),
+        ("CanBreakDoors", Some(V1_7), None),
+        ("IsChickenJockey", Some(V1_7_3), None),
+        ("OwnerUUID", Some(V1_7_6), None),
+        ("HurtByTimestamp", Some(V1_8), None),
+        ("NoAI", Some(V1_8), None),
+        ("ForcedAge", Some(V1_8), None),
+        ("EggLayTime", Some(V1_8), None),
+        ("wasOnGround", Some(V1_8), None),
+        ("PickupDelay", Some(V1_8), None),
+        ("Career", Some(V1_8), None),
+        ("Riding", None, Some(V1_8_9)),
+        ("Equipment", None, Some(V1_8_9)),
+        ("Passengers", Some(V1_9_4), None),
+        ("HandItems", Some(V1_9_4), None),
+        ("ArmorItems", Some(V1_9_4), None),
+        ("Glowing", Some(V1_9_4), None),
+    ]
+};
+
+const TILE_ENTITY_IDS: &[Evidence] = {
+    use MinecraftVersion::*;
+
+    &[
+        ("Hopper", Some(V1_5), None),
+        ("Comparator", Some(V1_5), None),
+        ("DLDetector", Some(V1_5), None),
+        ("Dropper", Some(V1_5), None),
+        ("FlowerPot", Some(V1_7), None),
+        ("Banner", Some(V1_8), None),
+        ("EndGateway", Some(V1_9_4), None),
+        ("Structure", Some(V1_9_4), None),
+    ]
+};
+
+const TILE_ENTITY_KEYS: &[Evidence] = {
+    use MinecraftVersion::*;
+
+    &[
+        ("CustomName", Some(V1_5), None),
+        ("TransferCooldown", Some(V1_5), None),
+        ("SuccessCount", Some(V1_7), None),
+        ("LastOutput", Some(V1_7), None),
+        ("ExtraType", None, Some(V1_7_5)),
+        ("Lock", Some(V1_8), None),
+        ("CookTimeTotal", Some(V1_8), None),
+        ("CommandStats", Some(V1_8), None),
+    ]
+};
+
+/// Blocks that share their id with older ones, as id, data mask, data and the version that
+/// added them. Whole new ids come from [`ids::since`].
+const BLOCK_VARIANTS: &[(u16, u8, u8, MinecraftVersion)] = {
+    use MinecraftVersion::*;
+
+    &[
+        // nether brick slab (double, lower, upper)
+        (43, 0xf, 6, V1_4_6),
+        (44, 0x7, 6, V1_4_6),
+        // quartz slab (double, lower, upper)
+        (43, 0xf, 7, V1_5),
+        (44, 0x7, 7, V1_5),
+        // coarse dirt & podzol
+        (3, 0xf, 1, V1_7),
+        (3, 0xf, 2, V1_7),
+        // acacia & dark oak planks, saplings and slabs
+        (5, 0xf, 4, V1_7),
+        (5, 0xf, 5, V1_7),
+        (6, 0x7, 4, V1_7),
+        (6, 0x7, 5, V1_7),
+        (125, 0x7, 4, V1_7),
+        (125, 0x7, 5, V1_7),
+        (126, 0x7, 4, V1_7),
+        (126, 0x7, 5, V1_7),
+        // red sand
+        (12, 0xf, 1, V1_7),
+        // blue orchid, allium, azure bluet, tulips and oxeye daisy
+        (38, 0xf, 1, V1_7),
+        (38, 0xf, 2, V1_7),
+        (38, 0xf, 3, V1_7),
+        (38, 0xf, 4, V1_7),
+        (38, 0xf, 5, V1_7),
+        (38, 0xf, 6, V1_7),
+        (38, 0xf, 7, V1_7),
+        (38, 0xf, 8, V1_7),
+        // infested mossy, cracked & chiseled stone bricks
+        (97, 0xf, 3, V1_7),
+        (97, 0xf, 4, V1_7),
+        (97, 0xf, 5, V1_7),
+        // granite, diorite, andesite and their polished versions
+        (1, 0xf, 1, V1_8),
+        (1, 0xf, 2, V1_8),
+        (1, 0xf, 3, V1_8),
+        (1, 0xf, 4, V1_8),
+        (1, 0xf, 5, V1_8),
+        (1, 0xf, 6, V1_8),
+        // wet sponge
+        (19, 0xf, 1, V1_8),
+    ]
+};
+
+/// The oldest and newest version that could have saved a chunk, and the evidence that
+/// narrowed each end the most.
+#[derive(Debug, Clone)]
+struct VersionRange {
+    min: MinecraftVersion,
+    max: MinecraftVersion,
+    min_evidence: Option<String>,
+    max_evidence: Option<String>,
+}
+
+impl Default for VersionRange {
+    fn default() -> Self {
+        Self {
+            min: MinecraftVersion::OLDEST,
+            max: MinecraftVersion::NEWEST,
+            min_evidence: None,
+            max_evidence: None,
         }
+    }
+}
 
-        let mut unique_block_iddata = HashSet::new();
-        for section in chunk["Sections"].as_list().unwrap() {
-            let section = section.as_compound().unwrap();
-
-            let blocks = section["Blocks"]
-                .as_byte_array()
-                .unwrap()
-                .clone()
-                .into_inner();
-            let mut blocks =
-                unsafe { std::mem::transmute::<&[i8], &[u8]>(blocks.as_slice()) }.iter();
-
-            let data = section["Data"]
-                .as_byte_array()
-                .unwrap()
-                .clone()
-                .into_inner();
-            let mut data = NibbleIter::from_iter(
-                unsafe { std::mem::transmute::<Vec<i8>, Vec<u8>>(data) }.into_iter(),
-            );
-
-            let add = section.get("Add").and_then(|a| a.as_byte_array());
-            if let Some(add) = add {
-                let add = add.clone().into_inner();
-                let mut add = NibbleIter::from_iter(
-                    unsafe { std::mem::transmute::<Vec<i8>, Vec<u8>>(add) }.into_iter(),
-                );
-
-                while let (Some(id), Some(data), Some(add)) =
-                    (blocks.next(), data.next(), add.next())
-                {
-                    unique_block_iddata
-                        .insert(BlockIdData((*id as i32) | ((add as i32) << 8), data));
-                }
-            } else {
-                while let (Some(id), Some(data)) = (blocks.next(), data.next()) {
-                    unique_block_iddata.insert(BlockIdData(*id as i32, data));
-                }
+impl VersionRange {
+    /// `evidence` describes what was found, it's only built when it narrows the range.
+    fn at_least(&mut self, version: MinecraftVersion, evidence: impl FnOnce() -> String) {
+        if version > self.min {
+            self.min = version;
+            self.min_evidence = Some(evidence());
+        }
+    }
+
+    fn at_most(&mut self, version: MinecraftVersion, evidence: impl FnOnce() -> String) {
+        if version < self.max {
+            self.max = version;
+            self.max_evidence = Some(evidence());
+        }
+    }
+
+    /// `what` names the kind of key or id for the evidence, like "entity key".
+    fn apply(&mut self, evidence: &[Evidence], what: &str, name: &str) {
+        for (_, min, max) in evidence.iter().filter(|(n, _, _)| *n == name) {
+            if let Some(min) = min {
+                self.at_least(*min, || format!("{what} {name}"));
+            }
+            if let Some(max) = max {
+                self.at_most(*max, || format!("{what} {name}"));
             }
         }
+    }
+}
+
+/// The position and the range of versions that could have saved a chunk.
+fn fingerprint_chunk(data: &[u8]) -> color_eyre::Result<(i32, i32, VersionRange)> {
+    let chunk: ChunkContainer = fastnbt::from_bytes(data)?;
+    let chunk = chunk.level;
+
+    let x = chunk
+        .get("xPos")
+        .and_then(Value::as_i64)
+        .ok_or_eyre("The chunk has no xPos.")? as i32;
+    let z = chunk
+        .get("zPos")
+        .and_then(Value::as_i64)
+        .ok_or_eyre("The chunk has no zPos.")? as i32;
 
-        for block in &unique_block_iddata {
-            let i = block.0;
-            let d = block.1;
+    let mut range = VersionRange::default();
 
-            // 1.4.6
-            // nether brick slab (double, lower, upper)
-            if (i == 43 && d == 6) || (i == 44 && (d == 6 || d == 14)) {
-                min_version = min_version.max(MinecraftVersion::V1_4_6 as _);
+    for key in chunk.keys() {
+        range.apply(LEVEL_KEYS, "level key", key);
+    }
+
+    if let Some(Value::List(tile_ticks)) = chunk.get("TileTicks") {
+        for tile_tick in tile_ticks {
+            let Value::Compound(tile_tick) = tile_tick else {
+                continue;
+            };
+
+            match tile_tick.get("i") {
+                Some(Value::Int(_)) => range.at_most(MinecraftVersion::V1_7_10, || {
+                    "numeric tile tick block id".into()
+                }),
+                Some(Value::String(_)) => range.at_least(MinecraftVersion::V1_8, || {
+                    "namespaced tile tick block id".into()
+                }),
+                _ => {}
             }
 
-            // 1.5
-            if i == 146 // trapped chest
-            || i == 147 || i == 148 // weighted pressure plate (light, heavy)
-            || i == 149 || i == 150 // redstone comparator (unpowered, powered)
-            || i == 151 // daylight detector
-            || i == 152 // block of redstone
-            || i == 153 // nether quartz ore
-            || i == 154 // hopper
-            || i == 155 // block of quartz
-            || i == 156 // quartz stairs
-            || i == 157 // activator rail
-            || i == 158 // dropper
-            // quartz slab (double, lower, upper)
-            || (i == 43 && d == 7) || (i == 44 && (d == 7 || d == 15))
-            {
-                min_version = min_version.max(MinecraftVersion::V1_5 as _);
+            if tile_tick.contains_key("p") {
+                range.at_least(MinecraftVersion::V1_8, || "tile tick priority".into());
             }
+        }
+    }
 
-            // 1.6
-            if i == 159 // stained clay
-            || i == 170 // hay bale
-            || i == 171 // carpet
-            || i == 172 // hardened clay
-            || i == 173 // block of coal
-            || 1 == 2
-            {
-                min_version = min_version.max(MinecraftVersion::V1_6 as _);
+    if let Some(Value::List(entities)) = chunk.get("Entities") {
+        for entity in entities {
+            if let Value::Compound(entity) = entity {
+                fingerprint_entity(entity, &mut range);
             }
+        }
+    }
+
+    if let Some(Value::List(tile_entities)) = chunk.get("TileEntities") {
+        for tile_entity in tile_entities {
+            let Value::Compound(tile_entity) = tile_entity else {
+                continue;
+            };
+
+            fingerprint_tile_entity(tile_entity, &mut range);
+        }
+    }
 
-            // 1.7
-            if (i == 3 && (d == 1 || d == 2)) // coarse dirt & podzol
-            || (i == 6 && (d == 4 || d == 5)) // saplings (acacia & roofed_oak)
-            || (i == 12 && (d == 1)) // red sand
-            // stained glass replaced locked chest
-            || i == 161 // leaves2
-            || i == 162 // log2
-            || i == 174 // packed ice
-            || i == 175 // double plant
-            // todo: new flowers & infested blocks
-            || 1 == 2
-            {
-                min_version = min_version.max(MinecraftVersion::V1_7 as _);
+    let mut unique_block_iddata = HashSet::new();
+    if let Some(Value::List(sections)) = chunk.get("Sections") {
+        for section in sections {
+            if let Value::Compound(section) = section {
+                unique_block_iddata.extend(read_section_blocks(section).unwrap_or_default());
             }
+        }
+    }
 
-            // todo: 1.7.1+
+    for &(id, data) in &unique_block_iddata {
+        // stained glass took the id of the locked chest, which old worlds can still have
+        if id != 95 {
+            if let Some(since) = ids::since(id as i16) {
+                range.at_least(since, || format!("block {}", ids::describe(id as i16, 0)));
+            }
         }
 
-        if min_version >= (MinecraftVersion::V1_7 as _) {
-            println!("provider.getOrGenerateChunk({x}, {z});");
+        for (_, _, _, since) in BLOCK_VARIANTS
+            .iter()
+            .filter(|(i, mask, d, _)| *i == id && data & mask == *d)
+        {
+            range.at_least(*since, || {
+                format!("block {}", ids::describe(id as i16, data as i16))
+            });
         }
+    }
 
-        // println!(
-        //     "{:?} - {x},{z} - from {:?} to {:?}",
-        //     path,
-        //     unsafe { std::mem::transmute::<u8, MinecraftVersion>(min_version) },
-        //     unsafe { std::mem::transmute::<u8, MinecraftVersion>(max_version) }
-        // );
-        // println!(
-        //     "{:?} to {:?}",
-        //     unsafe { std::mem::transmute::<u8, MinecraftVersion>(min_version) },
-        //     unsafe { std::mem::transmute::<u8, MinecraftVersion>(max_version) }
-        // );
-        // println!("{:?}", unique_block_iddata);
-
-        if 1 != 2 {
-            // panic!()
+    Ok((x, z, range))
+}
+
+fn fingerprint_entity(entity: &HashMap<String, Value>, range: &mut VersionRange) {
+    let id = match entity.get("id") {
+        Some(Value::String(id)) => id.as_str(),
+        _ => "",
+    };
+
+    range.apply(ENTITY_IDS, "entity", id);
+    for key in entity.keys() {
+        range.apply(ENTITY_KEYS, "entity key", key);
+    }
+    fingerprint_item_stacks(entity, range);
+
+    // hanging entities went from `Direction` to `Facing`, which also moved them
+    if matches!(id, "Painting" | "ItemFrame") {
+        if entity.contains_key("Facing") {
+            range.at_least(MinecraftVersion::V1_8, || format!("{id} with Facing"));
+        } else if entity.contains_key("Direction") {
+            range.at_most(MinecraftVersion::V1_7_10, || format!("{id} with Direction"));
         }
     }
+
+    // minecarts were one entity with a `Type` before 1.5
+    match id {
+        "Minecart" => range.at_most(MinecraftVersion::V1_4_7, || "Minecart with Type".into()),
+        "MinecartRideable" | "MinecartChest" | "MinecartFurnace" => {
+            range.at_least(MinecraftVersion::V1_5, || format!("entity {id}"))
+        }
+        _ => {}
+    }
+
+    if entity.contains_key("Leashed") {
+        range.at_least(MinecraftVersion::V1_6, || format!("{id} with Leashed"));
+    }
+
+    // riders are saved inside the entity they ride since 1.9
+    if let Some(Value::List(passengers)) = entity.get("Passengers") {
+        for passenger in passengers {
+            if let Value::Compound(passenger) = passenger {
+                fingerprint_entity(passenger, range);
+            }
+        }
+    }
+    if let Some(Value::Compound(riding)) = entity.get("Riding") {
+        fingerprint_entity(riding, range);
+    }
 }
 
-trait NbtValueExt {
-    fn as_list(&self) -> Option<&[Value]>;
-    fn as_compound(&self) -> Option<&HashMap<String, Value>>;
-    fn as_byte_array(&self) -> Option<&ByteArray>;
+fn fingerprint_tile_entity(tile_entity: &HashMap<String, Value>, range: &mut VersionRange) {
+    let id = match tile_entity.get("id") {
+        Some(Value::String(id)) => id.as_str(),
+        _ => "",
+    };
+
+    range.apply(TILE_ENTITY_IDS, "tile entity", id);
+    for key in tile_entity.keys() {
+        range.apply(TILE_ENTITY_KEYS, "tile entity key", key);
+    }
+    fingerprint_item_stacks(tile_entity, range);
+
+    // sign text is a chat component since 1.8, which old versions show as is
+    if id == "Sign" {
+        for key in ["Text1", "Text2", "Text3", "Text4"] {
+            let Some(Value::String(text)) = tile_entity.get(key) else {
+                continue;
+            };
+
+            match serde_json::from_str::<serde_json::Value>(text) {
+                Ok(
+                    serde_json::Value::String(_)
+                    | serde_json::Value::Object(_)
+                    | serde_json::Value::Array(_),
+                ) => range.at_least(MinecraftVersion::V1_8, || format!("JSON sign {key}")),
+                // `1` or `null` could be either
+                Ok(_) => {}
+                Err(_) => range.at_most(MinecraftVersion::V1_7_10, || format!("plain sign {key}")),
+            }
+        }
+    }
 }
 
-impl NbtValueExt for Value {
-    fn as_list(&self) -> Option<&[Value]> {
-        match self {
-            Value::List(list) => Some(list),
-            _ => None,
+/// Item ids are names from 1.8 on. Numbers don't tell much, 1.8 still reads and keeps the
+/// ones it has no name for.
+fn fingerprint_item_stacks(compound: &HashMap<String, Value>, range: &mut VersionRange) {
+    let mut item_stacks = vec![];
+    for key in ["Item", "Potion", "FireworksItem", "RecordItem"] {
+        if let Some(Value::Compound(item_stack)) = compound.get(key) {
+            item_stacks.push(item_stack);
+        }
+    }
+    for key in ["Items", "Equipment", "HandItems", "ArmorItems", "Inventory"] {
+        if let Some(Value::List(list)) = compound.get(key) {
+            item_stacks.extend(list.iter().filter_map(|item_stack| match item_stack {
+                Value::Compound(item_stack) => Some(item_stack),
+                _ => None,
+            }));
         }
     }
 
-    fn as_compound(&self) -> Option<&HashMap<String, Value>> {
-        match self {
-            Value::Compound(compound) => Some(compound),
-            _ => None,
+    for item_stack in item_stacks {
+        match item_stack.get("id") {
+            Some(Value::Short(id)) => {
+                if let Some(since) = ids::since(*id) {
+                    range.at_least(since, || format!("item {}", ids::describe(*id, 0)));
+                }
+            }
+            Some(Value::String(name)) => {
+                range.at_least(MinecraftVersion::V1_8, || {
+                    format!("namespaced item id {name}")
+                });
+                if let Some(since) = ids::new_to_old(name).and_then(ids::since) {
+                    range.at_least(since, || format!("item {name}"));
+                }
+            }
+            _ => {}
         }
     }
+}
 
-    fn as_byte_array(&self) -> Option<&ByteArray> {
-        match self {
-            Value::ByteArray(byte_array) => Some(byte_array),
-            _ => None,
+/// Prints fingerprints to stdout.
+pub fn print_fingerprints(
+    fingerprints: &[ChunkFingerprint],
+    format: FingerprintFormat,
+) -> color_eyre::Result<()> {
+    let mut out = io::stdout().lock();
+
+    match format {
+        FingerprintFormat::Table => {
+            let region_width = fingerprints
+                .iter()
+                .map(|fingerprint| fingerprint.region.len())
+                .chain(["region".len()])
+                .max()
+                .unwrap();
+            let min_evidence_width = fingerprints
+                .iter()
+                .map(|fingerprint| evidence(&fingerprint.min_evidence).len())
+                .chain(["min evidence".len()])
+                .max()
+                .unwrap();
+
+            writeln!(
+                out,
+                "{:region_width$}  {:>6}  {:>6}  {:<6}  {:<6}  {:min_evidence_width$}  max evidence",
+                "region", "x", "z", "min", "max", "min evidence"
+            )?;
+            for fingerprint in fingerprints {
+                writeln!(
+                    out,
+                    "{:region_width$}  {:>6}  {:>6}  {:<6}  {:<6}  {:min_evidence_width$}  {}",
+                    fingerprint.region,
+                    fingerprint.x,
+                    fingerprint.z,
+                    fingerprint.min,
+                    fingerprint.max,
+                    evidence(&fingerprint.min_evidence),
+                    evidence(&fingerprint.max_evidence),
+                )?;
+            }
+        }
+        FingerprintFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, fingerprints)?;
+            writeln!(out)?;
+        }
+        FingerprintFormat::Csv => {
+            writeln!(out, "region,x,z,min,max,min_evidence,max_evidence")?;
+            for fingerprint in fingerprints {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    csv_field(&fingerprint.region),
+                    fingerprint.x,
+                    fingerprint.z,
+                    fingerprint.min,
+                    fingerprint.max,
+                    csv_field(fingerprint.min_evidence.as_deref().unwrap_or_default()),
+                    csv_field(fingerprint.max_evidence.as_deref().unwrap_or_default()),
+                )?;
+            }
         }
     }
+
+    Ok(())
 }
 
-struct NibbleIter<I>
-where
-    I: Iterator<Item = u8>,
-{
-    inner: I,
-    last: u8,
-    last_lsb: bool,
+/// The chunks `filter` matches, grouped by region in the order they were fingerprinted.
+pub fn plan(fingerprints: &[ChunkFingerprint], filter: &VersionFilter) -> Vec<RegionPlan> {
+    let mut plan: Vec<RegionPlan> = vec![];
+
+    for fingerprint in fingerprints.iter().filter(|f| filter.matches(f)) {
+        let chunk = [fingerprint.x, fingerprint.z];
+        match plan.last_mut() {
+            Some(region) if region.region == fingerprint.region => region.chunks.push(chunk),
+            _ => plan.push(RegionPlan {
+                region: fingerprint.region.clone(),
+                chunks: vec![chunk],
+            }),
+        }
+    }
+
+    plan
 }
 
-impl<I> NibbleIter<I>
-where
-    I: Iterator<Item = u8>,
-{
-    fn from_iter(inner: I) -> Self {
-        Self {
-            inner,
-            last: 0,
-            last_lsb: false,
+/// Prints a regeneration plan to stdout.
+pub fn print_plan(plan: &[RegionPlan], format: FingerprintFormat) -> color_eyre::Result<()> {
+    let mut out = io::stdout().lock();
+
+    match format {
+        FingerprintFormat::Table => {
+            for region in plan {
+                let chunks: Vec<_> = region
+                    .chunks
+                    .iter()
+                    .map(|[x, z]| format!("{x},{z}"))
+                    .collect();
+                writeln!(
+                    out,
+                    "{} ({} chunks): {}",
+                    region.region,
+                    chunks.len(),
+                    chunks.join(" ")
+                )?;
+            }
+        }
+        FingerprintFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, plan)?;
+            writeln!(out)?;
+        }
+        FingerprintFormat::Csv => {
+            writeln!(out, "region,x,z")?;
+            for region in plan {
+                for [x, z] in &region.chunks {
+                    writeln!(out, "{},{x},{z}", csv_field(&region.region))?;
+                }
+            }
         }
     }
+
+    Ok(())
 }
 
-impl<I> Iterator for NibbleIter<I>
-where
-    I: Iterator<Item = u8>,
-{
-    type Item = u8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let value = if !self.last_lsb {
-            self.last = self.inner.next()?;
-            self.last & 0xf
-        } else {
-            self.last >> 4
+/// Copies a world folder or a region file from `input_path` to `output_path` without the
+/// planned chunks, so the server generates them again. Returns how many were left out.
+pub fn delete_planned_chunks(
+    input_path: &Path,
+    output_path: &Path,
+    plan: &[RegionPlan],
+) -> color_eyre::Result<usize> {
+    if input_path.is_file() {
+        let Some(region) = plan.first() else {
+            fs::copy(input_path, output_path)?;
+            return Ok(0);
         };
+        return copy_region_without(input_path, output_path, &region.chunks);
+    }
+
+    let mut deleted = 0;
+    for relative_path in world::walk_files(input_path)? {
+        let (input_path, output_path) = (
+            input_path.join(&relative_path),
+            output_path.join(&relative_path),
+        );
+        fs::create_dir_all(output_path.parent().unwrap())?;
+
+        let region = plan
+            .iter()
+            .find(|region| Path::new(&region.region) == relative_path);
+        match region {
+            Some(region) => {
+                deleted += copy_region_without(&input_path, &output_path, &region.chunks)?
+            }
+            None => {
+                fs::copy(&input_path, &output_path).wrap_err_with(|| {
+                    format!("Failed to copy {input_path:?} to {output_path:?}.")
+                })?;
+            }
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Rewrites a region without some chunks, leaving no gaps where they were.
+fn copy_region_without(
+    input_path: &Path,
+    output_path: &Path,
+    chunks: &[[i32; 2]],
+) -> color_eyre::Result<usize> {
+    let mut input = Region::from_stream(File::open(input_path)?)
+        .wrap_err_with(|| format!("Failed to read region {input_path:?}."))?;
+    let mut output = Region::new(
+        File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(output_path)?,
+    )?;
+
+    let mut deleted = 0;
+    for z in 0..32 {
+        for x in 0..32 {
+            let Some(data) = input.read_chunk(x, z)? else {
+                continue;
+            };
+
+            let planned = chunks.iter().any(|[chunk_x, chunk_z]| {
+                chunk_x.rem_euclid(32) as usize == x && chunk_z.rem_euclid(32) as usize == z
+            });
+            if planned {
+                deleted += 1;
+            } else {
+                output.write_chunk(x, z, &data)?;
+            }
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Renders the chunks of one dimension top down, one pixel per chunk coloured by its
+/// minimum version, and prints which colour is which version.
+pub fn write_png(
+    fingerprints: &[ChunkFingerprint],
+    dimension: &Dimension,
+    path: &Path,
+) -> color_eyre::Result<()> {
+    let fingerprints: Vec<_> = fingerprints
+        .iter()
+        .filter(|fingerprint| Dimension::of(Path::new(&fingerprint.region)) == *dimension)
+        .collect();
+    if fingerprints.is_empty() {
+        bail!("No chunks to render in {dimension:?}.");
+    }
+
+    let min_x = fingerprints.iter().map(|f| f.x).min().unwrap();
+    let max_x = fingerprints.iter().map(|f| f.x).max().unwrap();
+    let min_z = fingerprints.iter().map(|f| f.z).min().unwrap();
+    let max_z = fingerprints.iter().map(|f| f.z).max().unwrap();
+
+    // missing chunks stay transparent
+    let mut image = RgbaImage::new((max_x - min_x + 1) as u32, (max_z - min_z + 1) as u32);
+    let mut counts = BTreeMap::new();
+    for fingerprint in &fingerprints {
+        image.put_pixel(
+            (fingerprint.x - min_x) as u32,
+            (fingerprint.z - min_z) as u32,
+            version_colour(fingerprint.min),
+        );
+        *counts.entry(fingerprint.min).or_insert(0) += 1;
+    }
+
+    image
+        .save(path)
+        .wrap_err_with(|| format!("Failed to write {path:?}."))?;
+
+    let mut out = io::stdout().lock();
+    writeln!(
+        out,
+        "{path:?}: {}x{} pixels, chunk {min_x},{min_z} at the top left",
+        image.width(),
+        image.height()
+    )?;
+    writeln!(out, "{:<7}  {:<7}  chunks", "colour", "min")?;
+    for (version, count) in counts {
+        let Rgba([r, g, b, _]) = version_colour(version);
+        writeln!(out, "#{r:02x}{g:02x}{b:02x}  {version:<7}  {count}")?;
+    }
+
+    Ok(())
+}
+
+/// A hue per version, from red for the oldest to violet for the newest.
+fn version_colour(version: MinecraftVersion) -> Rgba<u8> {
+    let hue = version as u8 as f32 / MinecraftVersion::NEWEST as u8 as f32 * 280.0;
+
+    let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        _ => (x, 0.0, 1.0),
+    };
+
+    Rgba([(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, 255])
+}
+
+fn evidence(evidence: &Option<String>) -> &str {
+    evidence.as_deref().unwrap_or("-")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use MinecraftVersion::*;
+
+    fn nbt<const N: usize>(tags: [(&str, Value); N]) -> HashMap<String, Value> {
+        tags.into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    fn chunk<const N: usize>(x: i32, tags: [(&str, Value); N]) -> Vec<u8> {
+        let mut level = nbt(tags);
+        level.insert("xPos".to_string(), Value::Int(x));
+        level.insert("zPos".to_string(), Value::Int(0));
+
+        fastnbt::to_bytes(&nbt([("Level", Value::Compound(level))])).unwrap()
+    }
+
+    fn range<const N: usize>(tags: [(&str, Value); N]) -> VersionRange {
+        fingerprint_chunk(&chunk(0, tags)).unwrap().2
+    }
+
+    fn sign(text: &str) -> Value {
+        Value::List(vec![Value::Compound(nbt([
+            ("id", Value::String("Sign".into())),
+            ("Text1", Value::String(text.into())),
+        ]))])
+    }
+
+    #[test]
+    fn level_keys_and_tile_ticks() {
+        let namespaced = range([
+            ("InhabitedTime", Value::Long(0)),
+            // the first tick says nothing, the second is namespaced
+            (
+                "TileTicks",
+                Value::List(vec![
+                    Value::Compound(nbt([("t", Value::Int(0))])),
+                    Value::Compound(nbt([("i", Value::String("minecraft:water".into()))])),
+                ]),
+            ),
+        ]);
+        assert_eq!(
+            (namespaced.min, namespaced.max),
+            (V1_8, MinecraftVersion::NEWEST)
+        );
+        assert_eq!(
+            namespaced.min_evidence.as_deref(),
+            Some("namespaced tile tick block id")
+        );
+        assert_eq!(namespaced.max_evidence, None);
+
+        let numeric = range([(
+            "TileTicks",
+            Value::List(vec![Value::Compound(nbt([("i", Value::Int(8))]))]),
+        )]);
+        assert_eq!(numeric.max, V1_7_10);
+        assert_eq!(
+            numeric.max_evidence.as_deref(),
+            Some("numeric tile tick block id")
+        );
+    }
+
+    #[test]
+    fn entities_and_numeric_item_ids() {
+        // a 1.8 banner with an item 1.8 had no name for, which doesn't cap the range
+        let range = range([
+            (
+                "Entities",
+                Value::List(vec![Value::Compound(nbt([
+                    ("id", Value::String("Zombie".into())),
+                    (
+                        "Riding",
+                        Value::Compound(nbt([("id", Value::String("Rabbit".into()))])),
+                    ),
+                ]))]),
+            ),
+            (
+                "TileEntities",
+                Value::List(vec![Value::Compound(nbt([
+                    ("id", Value::String("Chest".into())),
+                    (
+                        "Items",
+                        Value::List(vec![Value::Compound(nbt([("id", Value::Short(1))]))]),
+                    ),
+                ]))]),
+            ),
+        ]);
+        assert_eq!((range.min, range.max), (V1_8, V1_8_9));
+        assert_eq!(range.min_evidence.as_deref(), Some("entity Rabbit"));
+        assert_eq!(range.max_evidence.as_deref(), Some("entity key Riding"));
+    }
+
+    #[test]
+    fn sign_text() {
+        let json = range([("TileEntities", sign(r#""hi""#))]);
+        assert_eq!((json.min, json.max), (V1_8, MinecraftVersion::NEWEST));
+        assert_eq!(json.min_evidence.as_deref(), Some("JSON sign Text1"));
+
+        let plain = range([("TileEntities", sign(r#""hi" she said"#))]);
+        assert_eq!((plain.min, plain.max), (MinecraftVersion::OLDEST, V1_7_10));
+        assert_eq!(plain.max_evidence.as_deref(), Some("plain sign Text1"));
+
+        let number = range([("TileEntities", sign("1"))]);
+        assert_eq!(
+            (number.min, number.max),
+            (MinecraftVersion::OLDEST, MinecraftVersion::NEWEST)
+        );
+    }
+
+    #[test]
+    fn planned_chunks_are_deleted() {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("pv1_dfu-fingerprint-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (input_path, output_path) = (dir.join("r.0.0.mca"), dir.join("output.mca"));
+
+        let mut input = Region::new(
+            File::options()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&input_path)
+                .unwrap(),
+        )
+        .unwrap();
+        let old = chunk(0, [("TileEntities", sign("hello"))]);
+        let new = chunk(1, [("TileEntities", sign(r#"{"text":"hello"}"#))]);
+        input.write_chunk(0, 0, &old).unwrap();
+        input.write_chunk(1, 0, &new).unwrap();
+        drop(input);
+
+        let fingerprints = fingerprint_path(&input_path).unwrap();
+        let plan = plan(
+            &fingerprints,
+            &VersionFilter {
+                min_version: Some(V1_8),
+                max_version: None,
+            },
+        );
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].region, "r.0.0.mca");
+        assert_eq!(plan[0].chunks, [[1, 0]]);
+
+        let deleted = delete_planned_chunks(&input_path, &output_path, &plan).unwrap();
+        let mut output = Region::from_stream(File::open(&output_path).unwrap()).unwrap();
+        let (kept, gone) = (
+            output.read_chunk(0, 0).unwrap(),
+            output.read_chunk(1, 0).unwrap(),
+        );
+        fs::remove_dir_all(&dir).unwrap();
 
-        self.last_lsb = !self.last_lsb;
-        Some(value)
+        assert_eq!(deleted, 1);
+        assert_eq!(kept, Some(old));
+        assert_eq!(gone, None);
     }
 }
@@ -0,0 +1,91 @@
+use crate::version::MinecraftVersion;
+
+/// A vanilla enchantment by numeric id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Enchantment {
+    pub id: i16,
+    pub max_level: i16,
+    pub since: MinecraftVersion,
+    /// What to give instead in versions before `since`, `None` removes it.
+    pub replacement: Option<i16>,
+}
+
+const fn enchantment(id: i16, max_level: i16, since: MinecraftVersion) -> Enchantment {
+    Enchantment {
+        id,
+        max_level,
+        since,
+        replacement: None,
+    }
+}
+
+const fn replaced(
+    id: i16,
+    max_level: i16,
+    since: MinecraftVersion,
+    replacement: i16,
+) -> Enchantment {
+    Enchantment {
+        id,
+        max_level,
+        since,
+        replacement: Some(replacement),
+    }
+}
+
+/// Every vanilla enchantment, sorted by id.
+pub const ENCHANTMENTS: &[Enchantment] = {
+    use MinecraftVersion::*;
+
+    &[
+        enchantment(0, 4, V1_4_5),   // protection
+        enchantment(1, 4, V1_4_5),   // fire_protection
+        enchantment(2, 4, V1_4_5),   // feather_falling
+        enchantment(3, 4, V1_4_5),   // blast_protection
+        enchantment(4, 4, V1_4_5),   // projectile_protection
+        enchantment(5, 3, V1_4_5),   // respiration
+        enchantment(6, 1, V1_4_5),   // aqua_affinity
+        enchantment(7, 3, V1_4_6),   // thorns
+        enchantment(8, 3, V1_8),     // depth_strider
+        enchantment(9, 2, V1_9_4),   // frost_walker
+        enchantment(16, 5, V1_4_5),  // sharpness
+        enchantment(17, 5, V1_4_5),  // smite
+        enchantment(18, 5, V1_4_5),  // bane_of_arthropods
+        enchantment(19, 2, V1_4_5),  // knockback
+        enchantment(20, 2, V1_4_5),  // fire_aspect
+        enchantment(21, 3, V1_4_5),  // looting
+        enchantment(32, 5, V1_4_5),  // efficiency
+        enchantment(33, 1, V1_4_5),  // silk_touch
+        enchantment(34, 3, V1_4_5),  // unbreaking
+        enchantment(35, 3, V1_4_5),  // fortune
+        enchantment(48, 5, V1_4_5),  // power
+        enchantment(49, 2, V1_4_5),  // punch
+        enchantment(50, 1, V1_4_5),  // flame
+        enchantment(51, 1, V1_4_5),  // infinity
+        enchantment(61, 3, V1_7),    // luck_of_the_sea
+        enchantment(62, 3, V1_7),    // lure
+        replaced(70, 1, V1_9_4, 34), // mending, unbreaking is the closest
+    ]
+};
+
+pub fn get(id: i16) -> Option<&'static Enchantment> {
+    ENCHANTMENTS
+        .binary_search_by_key(&id, |enchantment| enchantment.id)
+        .ok()
+        .map(|index| &ENCHANTMENTS[index])
+}
+
+/// The id and level of an enchantment in `target`, or `None` if it has to be removed.
+/// Levels are clamped to the maximum, as old clients can't name higher ones. Modded ids are
+/// kept as they are.
+pub fn downgrade(id: i16, level: i16, target: MinecraftVersion) -> Option<(i16, i16)> {
+    let Some(mut enchantment) = get(id) else {
+        return Some((id, level));
+    };
+
+    while target < enchantment.since {
+        enchantment = get(enchantment.replacement?)?;
+    }
+
+    Some((enchantment.id, level.min(enchantment.max_level)))
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::bail;
+use fastnbt::Value;
+
+use crate::blocks::BlockMap;
+use crate::fixers;
+use crate::items::ItemMap;
+use crate::mapping::Mapping;
+use crate::unknown::UnknownIds;
+use crate::version::MinecraftVersion;
+
+/// What a fixer is handed: an entity, a tile entity, a chunk's `Level` compound, the `Data`
+/// compound of level.dat or a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixerKind {
+    Level,
+    Entity,
+    TileEntity,
+    LevelData,
+    Player,
+}
+
+/// State shared by every fixer run on a world.
+#[derive(Clone)]
+pub struct FixContext {
+    pub target: MinecraftVersion,
+    /// Player names by lowercase, dashed uuid, for versions from before 1.7.6.
+    pub player_names: HashMap<String, String>,
+    /// Substitutions for blocks the target doesn't have.
+    pub blocks: BlockMap,
+    /// Substitutions for items the target doesn't have.
+    pub items: ItemMap,
+    /// What to do with namespaced ids that have no numeric id, and how often that happened.
+    pub unknown_ids: UnknownIds,
+}
+
+impl FixContext {
+    pub fn new(target: MinecraftVersion) -> Self {
+        Self::with_mapping(target, &Mapping::default())
+    }
+
+    pub fn with_mapping(target: MinecraftVersion, mapping: &Mapping) -> Self {
+        Self {
+            target,
+            player_names: HashMap::new(),
+            blocks: BlockMap::new(target, &mapping.blocks),
+            items: ItemMap::new(target, &mapping.items),
+            unknown_ids: UnknownIds::default(),
+        }
+    }
+
+    /// A copy for another thread, with nothing counted yet.
+    pub fn fork(&self) -> Self {
+        Self {
+            unknown_ids: UnknownIds::new(self.unknown_ids.policy),
+            ..self.clone()
+        }
+    }
+
+    /// Adds the counts of a [`FixContext::fork`] back.
+    pub fn merge(&mut self, other: FixContext) {
+        self.unknown_ids.merge(other.unknown_ids);
+    }
+}
+
+/// A single downgrade step for one kind of compound, like a DFU `DataFix` but in reverse.
+///
+/// A fixer reads data in the format of `from` (and anything newer, up to the next fixer
+/// touching the same data) and leaves it in the format of `to`. It only runs when the
+/// target version is `to` or older. A fixer can empty an entity or tile entity compound to
+/// drop it from the chunk.
+///
+/// Upgrade fixers are the same with `to` newer than `from`, and run when the target is `to`
+/// or newer.
+pub trait Fixer: Send + Sync {
+    /// Unique name, used to turn the fixer off with `--disable-fixer`.
+    fn name(&self) -> &str;
+
+    fn kind(&self) -> FixerKind;
+
+    /// The `id`s this fixer applies to, an empty slice matches every id.
+    fn ids(&self) -> &[&str];
+
+    fn from(&self) -> MinecraftVersion;
+
+    fn to(&self) -> MinecraftVersion;
+
+    fn fix(
+        &self,
+        id: &str,
+        compound: &mut HashMap<String, Value>,
+        ctx: &mut FixContext,
+    ) -> color_eyre::Result<()>;
+}
+
+/// A fixer backed by a plain function, which is what all of the built in fixers are.
+pub struct FnFixer {
+    pub name: &'static str,
+    pub kind: FixerKind,
+    pub ids: &'static [&'static str],
+    pub from: MinecraftVersion,
+    pub to: MinecraftVersion,
+    pub fix: fn(&str, &mut HashMap<String, Value>, &mut FixContext) -> color_eyre::Result<()>,
+}
+
+impl Fixer for FnFixer {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn kind(&self) -> FixerKind {
+        self.kind
+    }
+
+    fn ids(&self) -> &[&str] {
+        self.ids
+    }
+
+    fn from(&self) -> MinecraftVersion {
+        self.from
+    }
+
+    fn to(&self) -> MinecraftVersion {
+        self.to
+    }
+
+    fn fix(
+        &self,
+        id: &str,
+        compound: &mut HashMap<String, Value>,
+        ctx: &mut FixContext,
+    ) -> color_eyre::Result<()> {
+        (self.fix)(id, compound, ctx)
+    }
+}
+
+/// Which way a registry converts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Downgrade,
+    Upgrade,
+}
+
+/// Every known fixer, kept in the order they run (newest first when downgrading) and indexed
+/// by kind and id.
+#[derive(Default)]
+pub struct FixerRegistry {
+    direction: Direction,
+    fixers: Vec<Box<dyn Fixer>>,
+    by_id: HashMap<(FixerKind, String), Vec<usize>>,
+    any_id: HashMap<FixerKind, Vec<usize>>,
+}
+
+impl FixerRegistry {
+    pub fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            ..Self::default()
+        }
+    }
+
+    /// A registry holding all of the built in fixers.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new(Direction::Downgrade);
+        for fixer in fixers::all() {
+            registry.register(fixer);
+        }
+
+        registry
+    }
+
+    /// A registry holding all of the built in upgrade fixers.
+    pub fn with_upgrades() -> Self {
+        let mut registry = Self::new(Direction::Upgrade);
+        for fixer in fixers::upgrades() {
+            registry.register(fixer);
+        }
+
+        registry
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Adds a fixer. Fixers run newest `from` first (oldest first when upgrading), and in
+    /// registration order within the same version.
+    pub fn register(&mut self, fixer: impl Fixer + 'static) {
+        let index = self.fixers.partition_point(|other| match self.direction {
+            Direction::Downgrade => other.from() >= fixer.from(),
+            Direction::Upgrade => other.from() <= fixer.from(),
+        });
+        self.fixers.insert(index, Box::new(fixer));
+        self.reindex();
+    }
+
+    /// Removes the fixer called `name`, so its step is skipped.
+    pub fn disable(&mut self, name: &str) -> color_eyre::Result<()> {
+        let Some(index) = self.fixers.iter().position(|fixer| fixer.name() == name) else {
+            bail!("There is no fixer called {name:?}.");
+        };
+
+        self.fixers.remove(index);
+        self.reindex();
+
+        Ok(())
+    }
+
+    pub fn fixers(&self) -> impl Iterator<Item = &dyn Fixer> {
+        self.fixers.iter().map(|fixer| fixer.as_ref())
+    }
+
+    fn reindex(&mut self) {
+        self.by_id.clear();
+        self.any_id.clear();
+
+        for (index, fixer) in self.fixers.iter().enumerate() {
+            if fixer.ids().is_empty() {
+                self.any_id.entry(fixer.kind()).or_default().push(index);
+            }
+
+            for id in fixer.ids() {
+                self.by_id
+                    .entry((fixer.kind(), id.to_string()))
+                    .or_default()
+                    .push(index);
+            }
+        }
+    }
+
+    /// The first fixer after `after` that applies to `id` and the context's target.
+    fn next(
+        &self,
+        kind: FixerKind,
+        id: &str,
+        after: Option<usize>,
+        ctx: &FixContext,
+    ) -> Option<usize> {
+        let by_id = self.by_id.get(&(kind, id.to_string()));
+        let any_id = self.any_id.get(&kind);
+
+        by_id
+            .into_iter()
+            .chain(any_id)
+            .flatten()
+            .copied()
+            .filter(|index| after.is_none_or(|after| *index > after))
+            .filter(|index| match self.direction {
+                Direction::Downgrade => ctx.target <= self.fixers[*index].to(),
+                Direction::Upgrade => ctx.target >= self.fixers[*index].to(),
+            })
+            .min()
+    }
+
+    /// Runs every applicable fixer on `compound`. The id is read again after each fixer,
+    /// since a fixer may rename it (like the 1.5 minecart split being undone).
+    pub fn apply(
+        &self,
+        kind: FixerKind,
+        compound: &mut HashMap<String, Value>,
+        ctx: &mut FixContext,
+    ) -> color_eyre::Result<()> {
+        let mut last = None;
+        loop {
+            let id = match kind {
+                FixerKind::Entity | FixerKind::TileEntity => compound
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                _ => String::new(),
+            };
+
+            let Some(index) = self.next(kind, &id, last, ctx) else {
+                return Ok(());
+            };
+
+            self.fixers[index].fix(&id, compound, ctx)?;
+            last = Some(index);
+        }
+    }
+}
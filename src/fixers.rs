@@ -0,0 +1,1695 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::{eyre, OptionExt};
+use fastnbt::Value;
+use tracing::warn;
+
+use crate::chat;
+use crate::fixer::{FixContext, FixerKind, FnFixer};
+use crate::ids;
+use crate::item_tag;
+use crate::player;
+use crate::unknown::OldId;
+use crate::version::MinecraftVersion;
+use crate::NbtValueExt;
+
+const MOBS: &[&str] = &[
+    "Mob",
+    "Monster",
+    "Creeper",
+    "Skeleton",
+    "Spider",
+    "Giant",
+    "Zombie",
+    "Slime",
+    "Ghast",
+    "PigZombie",
+    "Enderman",
+    "CaveSpider",
+    "Silverfish",
+    "Blaze",
+    "LavaSlime",
+    "EnderDragon",
+    "WitherBoss",
+    "Bat",
+    "Witch",
+    "Pig",
+    "Sheep",
+    "Cow",
+    "Chicken",
+    "Squid",
+    "Wolf",
+    "MushroomCow",
+    "SnowMan",
+    "Ozelot",
+    "VillagerGolem",
+    "Villager",
+];
+
+/// How many characters a sign line could have before 1.8.
+const SIGN_LINE_LENGTH: usize = 15;
+
+const PASSIVE_MOBS: &[&str] = &[
+    "Pig",
+    "Sheep",
+    "Cow",
+    "Chicken",
+    "Wolf",
+    "MushroomCow",
+    "Ozelot",
+    "Villager",
+];
+
+/// All of the built in fixers, in the order they should run.
+pub fn all() -> Vec<FnFixer> {
+    use FixerKind::*;
+    use MinecraftVersion::*;
+
+    vec![
+        // 1.9.4 -> 1.8.9
+        fixer("level_data_1_9", LevelData, &[], V1_9_4, level_data_1_9),
+        fixer(
+            "tile_entities_1_9",
+            TileEntity,
+            &["EndGateway", "Structure"],
+            V1_9_4,
+            drop,
+        ),
+        // 1.8.1 -> 1.8
+        fixer("level_data_1_8_1", LevelData, &[], V1_8_1, level_data_1_8_1),
+        // 1.8 -> 1.7.10
+        fixer("level_data_1_8", LevelData, &[], V1_8, level_data_1_8),
+        fixer("player_1_8", Player, &[], V1_8, player_1_8),
+        fixer("player_living_1_8", Player, &[], V1_8, living_1_8),
+        fixer("tile_ticks_1_8", Level, &[], V1_8, tile_ticks_1_8),
+        fixer("item_entity_1_8", Entity, &["Item"], V1_8, item_entity_1_8),
+        fixer("hanging_facing_1_8", Entity, &[], V1_8, hanging_facing_1_8),
+        fixer(
+            "projectile_in_tile_1_8",
+            Entity,
+            &[
+                "Arrow",
+                "Snowball",
+                "ThrownEnderpearl",
+                "ThrownPotion",
+                "ThrownExpBottle",
+                "Fireball",
+                "SmallFireball",
+                "WitherSkull",
+            ],
+            V1_8,
+            projectile_in_tile_1_8,
+        ),
+        fixer(
+            "thrown_potion_1_8",
+            Entity,
+            &["ThrownPotion"],
+            V1_8,
+            thrown_potion_1_8,
+        ),
+        fixer(
+            "item_frame_1_8",
+            Entity,
+            &["ItemFrame"],
+            V1_8,
+            item_frame_1_8,
+        ),
+        fixer(
+            "falling_sand_1_8",
+            Entity,
+            &["FallingSand"],
+            V1_8,
+            falling_sand_1_8,
+        ),
+        fixer(
+            "fireworks_1_8",
+            Entity,
+            &["FireworksRocketEntity"],
+            V1_8,
+            fireworks_1_8,
+        ),
+        fixer(
+            "minecart_chest_1_8",
+            Entity,
+            &["MinecartChest"],
+            V1_8,
+            minecart_chest_1_8,
+        ),
+        fixer("living_1_8", Entity, MOBS, V1_8, living_1_8),
+        fixer(
+            "slime_1_8",
+            Entity,
+            &["Slime", "LavaSlime"],
+            V1_8,
+            slime_1_8,
+        ),
+        fixer(
+            "pig_zombie_1_8",
+            Entity,
+            &["PigZombie"],
+            V1_8,
+            pig_zombie_1_8,
+        ),
+        fixer("ageable_1_8", Entity, PASSIVE_MOBS, V1_8, ageable_1_8),
+        fixer("chicken_1_8", Entity, &["Chicken"], V1_8, chicken_1_8),
+        fixer("villager_1_8", Entity, &["Villager"], V1_8, villager_1_8),
+        fixer(
+            "inventory_1_8",
+            TileEntity,
+            &["Furnace", "Chest", "Trap", "Cauldron"],
+            V1_8,
+            inventory_1_8,
+        ),
+        fixer("furnace_1_8", TileEntity, &["Furnace"], V1_8, furnace_1_8),
+        fixer(
+            "record_player_1_8",
+            TileEntity,
+            &["RecordPlayer"],
+            V1_8,
+            record_player_1_8,
+        ),
+        fixer(
+            "lock_1_8",
+            TileEntity,
+            &["Trap", "Cauldron"],
+            V1_8,
+            lock_1_8,
+        ),
+        fixer("sign_1_8", TileEntity, &["Sign"], V1_8, sign_1_8),
+        fixer("banner_1_8", TileEntity, &["Banner"], V1_8, drop),
+        fixer(
+            "command_block_1_8",
+            TileEntity,
+            &["Control"],
+            V1_8,
+            command_block_1_8,
+        ),
+        // 1.7.6 -> 1.7.5
+        fixer(
+            "tameable_owner_1_7_6",
+            Entity,
+            &["Wolf", "Ozelot"],
+            V1_7_6,
+            tameable_owner_1_7_6,
+        ),
+        fixer(
+            "skull_owner_1_7_6",
+            TileEntity,
+            &["Skull"],
+            V1_7_6,
+            skull_owner_1_7_6,
+        ),
+        // 1.7.3 -> 1.7.2
+        fixer(
+            "chicken_jockey_1_7_3",
+            Entity,
+            &["Chicken"],
+            V1_7_3,
+            chicken_jockey_1_7_3,
+        ),
+        // 1.7 -> 1.6.4
+        fixer("level_data_1_7", LevelData, &[], V1_7, level_data_1_7),
+        fixer("level_1_7", Level, &[], V1_7, level_1_7),
+        fixer("item_entity_1_7", Entity, &["Item"], V1_7, item_entity_1_7),
+        fixer("arrow_1_7", Entity, &["Arrow"], V1_7, arrow_1_7),
+        fixer(
+            "zombie_1_7",
+            Entity,
+            &["Zombie", "PigZombie"],
+            V1_7,
+            zombie_1_7,
+        ),
+        fixer(
+            "command_block_1_7",
+            TileEntity,
+            &["Control"],
+            V1_7,
+            command_block_1_7,
+        ),
+        // 1.6 -> 1.5.2
+        fixer("level_1_6", Level, &[], V1_6, level_1_6),
+        fixer("level_data_1_6", LevelData, &[], V1_6, level_data_1_6),
+        fixer("player_living_1_6", Player, &[], V1_6, living_1_6),
+        fixer("living_1_6", Entity, MOBS, V1_6, living_1_6),
+        // 1.5 -> 1.4.7
+        fixer(
+            "falling_sand_1_5",
+            Entity,
+            &["FallingSand"],
+            V1_5,
+            falling_sand_1_5,
+        ),
+        fixer(
+            "minecart_1_5",
+            Entity,
+            &["MinecartRideable", "MinecartChest", "MinecartFurnace"],
+            V1_5,
+            minecart_1_5,
+        ),
+        fixer("custom_name_1_5", TileEntity, &[], V1_5, custom_name_1_5),
+        fixer("hopper_1_5", TileEntity, &["Hopper"], V1_5, hopper_1_5),
+        fixer("dropper_1_5", TileEntity, &["Dropper"], V1_5, dropper_1_5),
+        fixer(
+            "redstone_1_5",
+            TileEntity,
+            &["Comparator", "DLDetector"],
+            V1_5,
+            drop,
+        ),
+    ]
+}
+
+/// All of the built in upgrade fixers, in the order they should run. They undo what the
+/// downgrade fixers did, where that can be undone.
+pub fn upgrades() -> Vec<FnFixer> {
+    use FixerKind::*;
+    use MinecraftVersion::*;
+
+    vec![
+        // 1.4.7 -> 1.5
+        upgrade(
+            "minecart_1_5",
+            Entity,
+            &["Minecart"],
+            V1_5,
+            upgrade_minecart_1_5,
+        ),
+        // 1.5.2 -> 1.6
+        upgrade(
+            "level_data_1_6",
+            LevelData,
+            &[],
+            V1_6,
+            upgrade_level_data_1_6,
+        ),
+        // 1.7.10 -> 1.8
+        upgrade(
+            "level_data_1_8",
+            LevelData,
+            &[],
+            V1_8,
+            upgrade_level_data_1_8,
+        ),
+        upgrade("tile_ticks_1_8", Level, &[], V1_8, upgrade_tile_ticks_1_8),
+        upgrade(
+            "hanging_facing_1_8",
+            Entity,
+            &["Painting", "ItemFrame"],
+            V1_8,
+            upgrade_hanging_facing_1_8,
+        ),
+        upgrade(
+            "projectile_in_tile_1_8",
+            Entity,
+            &[
+                "Arrow",
+                "Snowball",
+                "ThrownEnderpearl",
+                "ThrownPotion",
+                "ThrownExpBottle",
+                "Fireball",
+                "SmallFireball",
+                "WitherSkull",
+            ],
+            V1_8,
+            upgrade_projectile_in_tile_1_8,
+        ),
+        upgrade(
+            "falling_sand_1_8",
+            Entity,
+            &["FallingSand"],
+            V1_8,
+            upgrade_falling_sand_1_8,
+        ),
+        upgrade(
+            "item_stacks_1_8",
+            Entity,
+            &[],
+            V1_8,
+            upgrade_item_stacks_1_8,
+        ),
+        upgrade(
+            "tile_entity_item_stacks_1_8",
+            TileEntity,
+            &[],
+            V1_8,
+            upgrade_item_stacks_1_8,
+        ),
+        upgrade(
+            "player_item_stacks_1_8",
+            Player,
+            &[],
+            V1_8,
+            upgrade_item_stacks_1_8,
+        ),
+        upgrade("sign_1_8", TileEntity, &["Sign"], V1_8, upgrade_sign_1_8),
+    ]
+}
+
+fn fixer(
+    name: &'static str,
+    kind: FixerKind,
+    ids: &'static [&'static str],
+    from: MinecraftVersion,
+    fix: fn(&str, &mut HashMap<String, Value>, &mut FixContext) -> color_eyre::Result<()>,
+) -> FnFixer {
+    FnFixer {
+        name,
+        kind,
+        ids,
+        from,
+        to: from.previous().unwrap(),
+        fix,
+    }
+}
+
+fn upgrade(
+    name: &'static str,
+    kind: FixerKind,
+    ids: &'static [&'static str],
+    to: MinecraftVersion,
+    fix: fn(&str, &mut HashMap<String, Value>, &mut FixContext) -> color_eyre::Result<()>,
+) -> FnFixer {
+    FnFixer {
+        name,
+        kind,
+        ids,
+        from: to.previous().unwrap(),
+        to,
+        fix,
+    }
+}
+
+fn level_data_1_9(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    data.remove("DataVersion");
+    data.remove("Version");
+
+    // the lowercase `version` is the anvil format version (19133) and has to stay, old
+    // versions take a level.dat without it for a McRegion world and try to convert it
+
+    remove_game_rules(
+        data,
+        &[
+            "spectatorsGenerateChunks",
+            "spawnRadius",
+            "disableElytraMovementCheck",
+        ],
+    );
+
+    Ok(())
+}
+
+fn level_data_1_8_1(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    remove_game_rules(data, &["doEntityDrops"]);
+
+    Ok(())
+}
+
+fn level_data_1_8(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    // world border
+    data.remove("BorderCenterX");
+    data.remove("BorderCenterZ");
+    data.remove("BorderSize");
+    data.remove("BorderSafeZone");
+    data.remove("BorderWarningBlocks");
+    data.remove("BorderWarningTime");
+    data.remove("BorderSizeLerpTarget");
+    data.remove("BorderSizeLerpTime");
+    data.remove("BorderDamagePerBlock");
+
+    data.remove("Difficulty");
+    data.remove("DifficultyLocked");
+    data.remove("clearWeatherTime");
+
+    remove_game_rules(
+        data,
+        &[
+            "logAdminCommands",
+            "showDeathMessages",
+            "randomTickSpeed",
+            "sendCommandFeedback",
+            "reducedDebugInfo",
+        ],
+    );
+
+    if let Some(Value::String(generator_name)) = data.get("generatorName") {
+        if generator_name == "customized" || generator_name == "debug_all_block_states" {
+            data.insert("generatorName".into(), Value::String("default".into()));
+            data.remove("generatorOptions");
+        }
+    }
+
+    downgrade_game_type(data, "GameType");
+
+    Ok(())
+}
+
+fn level_data_1_7(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(Value::String(generator_name)) = data.get("generatorName") {
+        if generator_name == "amplified" {
+            data.insert("generatorName".into(), Value::String("default".into()));
+        }
+    }
+
+    Ok(())
+}
+
+fn level_data_1_6(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    remove_game_rules(data, &["naturalRegeneration", "doDaylightCycle"]);
+
+    Ok(())
+}
+
+fn remove_game_rules(data: &mut HashMap<String, Value>, names: &[&str]) {
+    if let Some(game_rules) = data.get_mut("GameRules").and_then(NbtValueExt::as_compound) {
+        for name in names {
+            game_rules.remove(*name);
+        }
+    }
+}
+
+/// Spectator mode (3) came with 1.8, adventure is the closest thing old versions have.
+fn downgrade_game_type(compound: &mut HashMap<String, Value>, key: &str) {
+    if compound.get(key).and_then(Value::as_i64) == Some(3) {
+        compound.insert(key.into(), Value::Int(2));
+    }
+}
+
+fn player_1_8(
+    _id: &str,
+    player: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    for key in ["Inventory", "EnderItems"] {
+        if let Some(items) = player.get_mut(key).and_then(|i| i.as_list()) {
+            downgrade_item_stacks(items, ctx)?;
+        }
+    }
+
+    player.remove("XpSeed");
+    downgrade_game_type(player, "playerGameType");
+
+    Ok(())
+}
+
+fn tile_ticks_1_8(
+    _id: &str,
+    level: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let tile_ticks = level.get_mut("TileTicks").and_then(|t| t.as_list());
+    if let Some(tile_ticks) = tile_ticks {
+        for tile_tick in tile_ticks.iter_mut() {
+            let tile_tick = tile_tick
+                .as_compound()
+                .ok_or_eyre("A tile tick isn't a compound.")?;
+
+            if let Some(Value::String(id)) = tile_tick.get("i") {
+                match ctx.unknown_ids.old_block_id(id, "tile tick")? {
+                    OldId::Id(id) => {
+                        tile_tick.insert("i".into(), Value::Int(id as _));
+                    }
+                    OldId::Drop => tile_tick.clear(),
+                    OldId::Keep => {}
+                }
+            }
+        }
+
+        tile_ticks.retain(|tile_tick| !matches!(tile_tick, Value::Compound(c) if c.is_empty()));
+    }
+
+    Ok(())
+}
+
+fn item_entity_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("PickupDelay");
+
+    let item = entity
+        .get_mut("Item")
+        .and_then(NbtValueExt::as_compound)
+        .ok_or_eyre("The item entity has no Item compound.")?;
+    downgrade_item_stack(item, ctx)?;
+
+    // an item entity without its item is nothing
+    if item.is_empty() {
+        entity.clear();
+    }
+
+    Ok(())
+}
+
+/// Decoration entities (painting & item frame) went from `Direction` to `Facing`, which
+/// also moved the block they hang on.
+fn hanging_facing_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let Some(facing) = entity.get("Facing") else {
+        return Ok(());
+    };
+
+    let facing = facing.as_i64().ok_or_eyre("Facing isn't a number.")? as i8;
+    entity.remove("Facing");
+    entity.insert("Direction".into(), Value::Byte(facing));
+
+    match facing {
+        0 => {
+            let z = tile_coordinate(entity, "TileZ")?;
+            entity.insert("TileZ".into(), Value::Int(z - 1));
+        }
+        1 => {
+            let x = tile_coordinate(entity, "TileX")?;
+            entity.insert("TileX".into(), Value::Int(x - -1));
+        }
+        2 => {
+            let z = tile_coordinate(entity, "TileZ")?;
+            entity.insert("TileZ".into(), Value::Int(z - -1));
+        }
+        3 => {
+            let x = tile_coordinate(entity, "TileX")?;
+            entity.insert("TileX".into(), Value::Int(x - 1));
+        }
+
+        _ => {}
+    }
+
+    // item frame rotation
+    if let Some(Value::Byte(item_rotation)) = entity.get_mut("ItemRotation") {
+        *item_rotation /= 2;
+    }
+
+    Ok(())
+}
+
+fn projectile_in_tile_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(Value::String(in_tile)) = entity.get("inTile") {
+        let in_tile = match ctx.unknown_ids.old_block_id(in_tile, "projectile inTile")? {
+            OldId::Id(id) => ctx.blocks.get(id as u16, 0).0,
+            OldId::Drop => 0,
+            OldId::Keep => return Ok(()),
+        };
+
+        entity.insert("inTile".into(), Value::Byte(in_tile as i8));
+    }
+
+    Ok(())
+}
+
+fn thrown_potion_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(potion) = entity.get_mut("Potion").and_then(NbtValueExt::as_compound) {
+        downgrade_item_stack(potion, ctx)?;
+
+        if potion.is_empty() {
+            entity.clear();
+        }
+    }
+
+    Ok(())
+}
+
+fn item_frame_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(item) = entity.get_mut("Item").and_then(NbtValueExt::as_compound) {
+        downgrade_item_stack(item, ctx)?;
+
+        // an empty frame
+        if item.is_empty() {
+            entity.remove("Item");
+        }
+    }
+
+    Ok(())
+}
+
+fn falling_sand_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let Some(Value::String(id)) = entity.get("Block") else {
+        return Ok(());
+    };
+
+    match ctx.unknown_ids.old_block_id(id, "falling block")? {
+        OldId::Id(id) => {
+            entity.remove("Block");
+            entity.insert("Tile".into(), Value::Byte(id as i8));
+            // the byte reads back negative, 1.6 and newer read the int instead
+            if id > i8::MAX as i16 {
+                entity.insert("TileID".into(), Value::Int(id as i32));
+            }
+        }
+        OldId::Drop => entity.clear(),
+        OldId::Keep => {}
+    }
+
+    Ok(())
+}
+
+fn fireworks_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let item = entity
+        .get_mut("FireworksItem")
+        .and_then(NbtValueExt::as_compound)
+        .ok_or_eyre("The firework has no FireworksItem compound.")?;
+    downgrade_item_stack(item, ctx)?;
+
+    if item.is_empty() {
+        entity.clear();
+    }
+
+    Ok(())
+}
+
+fn minecart_chest_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let items = entity
+        .get_mut("Items")
+        .and_then(NbtValueExt::as_list)
+        .ok_or_eyre("Items isn't a list.")?;
+    downgrade_item_stacks(items, ctx)
+}
+
+fn living_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    // living entity
+    entity.remove("HurtByTimestamp");
+
+    if let Some(active_effects) = entity.get_mut("ActiveEffects").and_then(|e| e.as_list()) {
+        for effect in active_effects {
+            if let Some(effect) = effect.as_compound() {
+                effect.remove("ShowParticles");
+            }
+        }
+    }
+
+    // mob entity (players have no equipment list), empty slots are empty compounds
+    if let Some(equipment) = entity.get_mut("Equipment").and_then(|e| e.as_list()) {
+        for equipment in equipment {
+            let equipment = equipment
+                .as_compound()
+                .ok_or_eyre("An equipment slot isn't a compound.")?;
+            downgrade_item_stack(equipment, ctx)?;
+        }
+    }
+
+    entity.remove("NoAI");
+
+    Ok(())
+}
+
+fn slime_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("wasOnGround");
+
+    Ok(())
+}
+
+fn pig_zombie_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("HurtBy");
+
+    Ok(())
+}
+
+fn ageable_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("ForcedAge");
+
+    Ok(())
+}
+
+fn chicken_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("EggLayTime");
+
+    Ok(())
+}
+
+fn villager_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("Career");
+    entity.remove("CareerLevel");
+    entity.remove("Willing");
+    entity.remove("Inventory");
+
+    if let Some(offers) = entity.get_mut("Offers").and_then(NbtValueExt::as_compound) {
+        let recipes = offers
+            .get_mut("Recipes")
+            .and_then(NbtValueExt::as_list)
+            .ok_or_eyre("Recipes isn't a list.")?;
+        for recipe in recipes.iter_mut() {
+            let recipe = recipe
+                .as_compound()
+                .ok_or_eyre("A recipe isn't a compound.")?;
+
+            recipe.remove("rewardExp");
+
+            let mut dropped = false;
+            for key in ["buy", "sell", "buyB"] {
+                if let Some(item) = recipe.get_mut(key).and_then(NbtValueExt::as_compound) {
+                    downgrade_item_stack(item, ctx)?;
+                    dropped |= item.is_empty();
+                }
+            }
+
+            // a trade missing one of its items can't be made
+            if dropped {
+                recipe.clear();
+            }
+        }
+
+        recipes.retain(|recipe| !matches!(recipe, Value::Compound(c) if c.is_empty()));
+    }
+
+    Ok(())
+}
+
+fn inventory_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let items = tile_entity
+        .get_mut("Items")
+        .and_then(NbtValueExt::as_list)
+        .ok_or_eyre("Items isn't a list.")?;
+    downgrade_item_stacks(items, ctx)
+}
+
+fn furnace_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.remove("CookTimeTotal");
+
+    Ok(())
+}
+
+fn record_player_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(item) = tile_entity
+        .get_mut("RecordItem")
+        .and_then(NbtValueExt::as_compound)
+    {
+        downgrade_item_stack(item, ctx)?;
+
+        if item.is_empty() {
+            tile_entity.remove("RecordItem");
+            tile_entity.remove("Record");
+        }
+    }
+
+    Ok(())
+}
+
+/// Lockable containers.
+fn lock_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.remove("Lock");
+
+    Ok(())
+}
+
+fn sign_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    downgrade_sign_text(tile_entity, "Text1")?;
+    downgrade_sign_text(tile_entity, "Text2")?;
+    downgrade_sign_text(tile_entity, "Text3")?;
+    downgrade_sign_text(tile_entity, "Text4")?;
+
+    Ok(())
+}
+
+fn command_block_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.remove("CommandStats");
+
+    Ok(())
+}
+
+/// Owners were player names before 1.7.6, the uuid is kept when no name is known.
+fn tameable_owner_1_7_6(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(owner_id) = entity.remove("OwnerUUID") {
+        // the names are keyed like playerdata files, owners may be written differently
+        let owner = match owner_id
+            .as_str()
+            .and_then(|uuid| ctx.player_names.get(&player::normalize_uuid(uuid)))
+        {
+            Some(name) => Value::String(name.clone()),
+            None => owner_id,
+        };
+
+        entity.insert("Owner".into(), owner);
+    }
+
+    Ok(())
+}
+
+/// Player skulls stored the owner name as a plain string before game profiles.
+fn skull_owner_1_7_6(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(Value::Compound(owner)) = tile_entity.remove("Owner") {
+        if let Some(Value::String(name)) = owner.get("Name") {
+            tile_entity.insert("ExtraType".into(), Value::String(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+fn chicken_jockey_1_7_3(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("IsChickenJockey"); // (1.7.5 kinda)
+
+    Ok(())
+}
+
+fn level_1_7(
+    _id: &str,
+    level: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    level.remove("V");
+    level.remove("LightPopulated");
+
+    Ok(())
+}
+
+fn item_entity_1_7(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("Thrower");
+    entity.remove("Owner");
+
+    Ok(())
+}
+
+fn arrow_1_7(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("life");
+
+    Ok(())
+}
+
+fn zombie_1_7(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("CanBreakDoors");
+
+    Ok(())
+}
+
+fn command_block_1_7(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.remove("SuccessCount");
+    tile_entity.remove("TrackOutput");
+    tile_entity.remove("LastOutput");
+
+    Ok(())
+}
+
+fn level_1_6(
+    _id: &str,
+    level: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    level.remove("InhabitedTime");
+
+    Ok(())
+}
+
+fn living_1_6(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    // living entity
+    entity.remove("HealF");
+    entity.remove("Attributes");
+    entity.remove("AbsorptionAmount");
+
+    // mob entity
+    entity.remove("Leashed");
+    entity.remove("Leash");
+
+    Ok(())
+}
+
+fn falling_sand_1_5(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    entity.remove("TileEntityData");
+
+    Ok(())
+}
+
+/// Minecarts were a single entity with a `Type` before 1.5.
+fn minecart_1_5(
+    id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let minecart_type = match id {
+        "MinecartRideable" => 0,
+        "MinecartChest" => 1,
+        "MinecartFurnace" => 2,
+        _ => return Ok(()),
+    };
+
+    entity.insert("id".into(), Value::String("Minecart".into()));
+    entity.insert("Type".into(), Value::Int(minecart_type));
+
+    Ok(())
+}
+
+fn custom_name_1_5(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.remove("CustomName");
+
+    // should probably do mob spawners, but uh
+    // no
+
+    Ok(())
+}
+
+/// Hoppers become chests, so keep their items in one.
+fn hopper_1_5(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.insert("id".into(), Value::String("Chest".into()));
+    tile_entity.remove("TransferCooldown");
+
+    Ok(())
+}
+
+/// Droppers become dispensers.
+fn dropper_1_5(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    tile_entity.insert("id".into(), Value::String("Trap".into()));
+
+    Ok(())
+}
+
+/// For tile entities whose block is substituted by one without a tile entity.
+fn drop(
+    _id: &str,
+    compound: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    compound.clear();
+
+    Ok(())
+}
+
+/// Flattens a line of 1.8 JSON sign text and fits it in [`SIGN_LINE_LENGTH`], dropping the
+/// formatting first and then cutting off text, which is logged.
+fn downgrade_sign_text(
+    sign: &mut HashMap<String, Value>,
+    text_key: &str,
+) -> color_eyre::Result<()> {
+    let text = sign
+        .remove(text_key)
+        .and_then(|value| value.as_str().map(|s| s.to_owned()))
+        .unwrap_or("".to_owned());
+
+    // 1.8 reads text that isn't JSON as is
+    let mut text = serde_json::from_str::<serde_json::Value>(&text)
+        .map(|component| chat::to_legacy(&component))
+        .unwrap_or(text);
+
+    let stripped = chat::strip_codes(&text);
+    let shortened = if stripped.chars().count() > SIGN_LINE_LENGTH {
+        Some(chat::truncate(&stripped, SIGN_LINE_LENGTH))
+    } else if text.chars().count() > SIGN_LINE_LENGTH {
+        // the codes count towards the length, so they go before any text does
+        Some(stripped)
+    } else {
+        None
+    };
+
+    if let Some(shortened) = shortened {
+        let position = ["x", "y", "z"].map(|key| match sign.get(key) {
+            Some(Value::Int(coordinate)) => coordinate.to_string(),
+            _ => "?".into(),
+        });
+        warn!(
+            "Shortened {text_key} of the sign at {} from {text:?} to {shortened:?}",
+            position.join(", ")
+        );
+        text = shortened;
+    }
+
+    sign.insert(text_key.into(), Value::String(text));
+
+    Ok(())
+}
+
+/// Downgrades every stack in an inventory list, removing the dropped ones.
+pub fn downgrade_item_stacks(
+    item_stacks: &mut Vec<Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    for item_stack in item_stacks.iter_mut() {
+        let item_stack = item_stack
+            .as_compound()
+            .ok_or_eyre("An item stack isn't a compound.")?;
+        downgrade_item_stack(item_stack, ctx)?;
+    }
+
+    item_stacks.retain(|item_stack| !matches!(item_stack, Value::Compound(c) if c.is_empty()));
+
+    Ok(())
+}
+
+/// Turns a namespaced id into a numeric one, substitutes items the target doesn't have and
+/// converts their tag. A dropped unknown or too new item leaves an empty compound behind.
+pub fn downgrade_item_stack(
+    item_stack: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(Value::String(ident)) = item_stack.get("id") {
+        // a replacement doesn't take the damage of what it replaces
+        let replaced = ids::new_to_old(ident).is_none();
+        match ctx.unknown_ids.old_id(ident, "item stack")? {
+            OldId::Id(id) => {
+                item_stack.insert("id".into(), Value::Short(id));
+                if replaced {
+                    item_stack.insert("Damage".into(), Value::Short(0));
+                }
+            }
+            OldId::Drop => {
+                item_stack.clear();
+                return Ok(());
+            }
+            OldId::Keep => {}
+        }
+    }
+
+    if let Some(Value::Short(id)) = item_stack.get("id") {
+        let damage = match item_stack.get("Damage") {
+            Some(Value::Short(damage)) => *damage,
+            _ => 0,
+        };
+
+        let (mut id, mut damage) = ctx.items.get(*id, damage);
+        if !ids::exists_in(id, ctx.target) {
+            match ctx.unknown_ids.too_new(id, ctx.target, "item stack")? {
+                OldId::Id(replacement) => (id, damage) = (replacement, 0),
+                OldId::Drop => {
+                    item_stack.clear();
+                    return Ok(());
+                }
+                OldId::Keep => {}
+            }
+        }
+
+        item_stack.insert("id".into(), Value::Short(id));
+        item_stack.insert("Damage".into(), Value::Short(damage));
+    }
+
+    let id = match item_stack.get("id") {
+        Some(Value::Short(id)) => Some(*id),
+        _ => None,
+    };
+    if let Some(tag) = item_stack.get_mut("tag").and_then(NbtValueExt::as_compound) {
+        item_tag::downgrade_item_tag(id, tag, ctx)?;
+
+        if tag.is_empty() {
+            item_stack.remove("tag");
+        }
+    }
+
+    Ok(())
+}
+
+/// The reverse of [`level_data_1_6`], with the defaults of the new game rules.
+fn upgrade_level_data_1_6(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    add_game_rules(
+        data,
+        &[("naturalRegeneration", "true"), ("doDaylightCycle", "true")],
+    );
+
+    Ok(())
+}
+
+/// The reverse of [`level_data_1_8`]: the default world border, weather and game rules.
+/// The difficulty stays in the server properties until it's changed in game.
+fn upgrade_level_data_1_8(
+    _id: &str,
+    data: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    for (key, value) in [
+        ("BorderCenterX", Value::Double(0.0)),
+        ("BorderCenterZ", Value::Double(0.0)),
+        ("BorderSize", Value::Double(60_000_000.0)),
+        ("BorderSafeZone", Value::Double(5.0)),
+        ("BorderWarningBlocks", Value::Double(5.0)),
+        ("BorderWarningTime", Value::Double(15.0)),
+        ("BorderSizeLerpTarget", Value::Double(60_000_000.0)),
+        ("BorderSizeLerpTime", Value::Long(0)),
+        ("BorderDamagePerBlock", Value::Double(0.2)),
+        ("clearWeatherTime", Value::Int(0)),
+    ] {
+        data.entry(key.into()).or_insert(value);
+    }
+
+    add_game_rules(
+        data,
+        &[
+            ("logAdminCommands", "true"),
+            ("showDeathMessages", "true"),
+            ("randomTickSpeed", "3"),
+            ("sendCommandFeedback", "true"),
+            ("reducedDebugInfo", "false"),
+        ],
+    );
+
+    Ok(())
+}
+
+fn add_game_rules(data: &mut HashMap<String, Value>, rules: &[(&str, &str)]) {
+    if let Some(game_rules) = data.get_mut("GameRules").and_then(NbtValueExt::as_compound) {
+        for (name, value) in rules {
+            game_rules
+                .entry(name.to_string())
+                .or_insert_with(|| Value::String(value.to_string()));
+        }
+    }
+}
+
+/// The reverse of [`minecart_1_5`].
+fn upgrade_minecart_1_5(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let id = match entity.get("Type").and_then(Value::as_i64) {
+        Some(0) => "MinecartRideable",
+        Some(1) => "MinecartChest",
+        Some(2) => "MinecartFurnace",
+        _ => return Ok(()),
+    };
+
+    entity.remove("Type");
+    entity.insert("id".into(), Value::String(id.into()));
+
+    Ok(())
+}
+
+fn upgrade_tile_ticks_1_8(
+    _id: &str,
+    level: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let tile_ticks = level.get_mut("TileTicks").and_then(|t| t.as_list());
+    if let Some(tile_ticks) = tile_ticks {
+        for tile_tick in tile_ticks.iter_mut() {
+            let tile_tick = tile_tick
+                .as_compound()
+                .ok_or_eyre("A tile tick isn't a compound.")?;
+
+            if let Some(id) = tile_tick.get("i").and_then(Value::as_i64) {
+                if let Some(name) = ids::old_to_new(id as i16) {
+                    tile_tick.insert("i".into(), Value::String(name.into()));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A hanging entity's `TileX` or `TileZ`.
+fn tile_coordinate(entity: &HashMap<String, Value>, key: &str) -> color_eyre::Result<i32> {
+    entity
+        .get(key)
+        .and_then(Value::as_i64)
+        .map(|coordinate| coordinate as i32)
+        .ok_or_else(|| eyre!("The hanging entity has no {key}."))
+}
+
+/// The reverse of [`hanging_facing_1_8`], moving the entity back off the block it hangs on.
+fn upgrade_hanging_facing_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let Some(direction) = entity.get("Direction") else {
+        return Ok(());
+    };
+
+    let direction = direction.as_i64().ok_or_eyre("Direction isn't a number.")? as i8;
+    entity.remove("Direction");
+    entity.insert("Facing".into(), Value::Byte(direction));
+
+    match direction {
+        0 => {
+            let z = tile_coordinate(entity, "TileZ")?;
+            entity.insert("TileZ".into(), Value::Int(z + 1));
+        }
+        1 => {
+            let x = tile_coordinate(entity, "TileX")?;
+            entity.insert("TileX".into(), Value::Int(x - 1));
+        }
+        2 => {
+            let z = tile_coordinate(entity, "TileZ")?;
+            entity.insert("TileZ".into(), Value::Int(z - 1));
+        }
+        3 => {
+            let x = tile_coordinate(entity, "TileX")?;
+            entity.insert("TileX".into(), Value::Int(x + 1));
+        }
+
+        _ => {}
+    }
+
+    // item frame rotation
+    if let Some(Value::Byte(item_rotation)) = entity.get_mut("ItemRotation") {
+        *item_rotation *= 2;
+    }
+
+    Ok(())
+}
+
+fn upgrade_projectile_in_tile_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    if let Some(Value::Byte(in_tile)) = entity.get("inTile") {
+        if let Some(name) = ids::old_to_new(*in_tile as u8 as i16) {
+            entity.insert("inTile".into(), Value::String(name.into()));
+        }
+    }
+
+    Ok(())
+}
+
+fn upgrade_falling_sand_1_8(
+    _id: &str,
+    entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    // 1.6 added the int `TileID` next to the byte `Tile`
+    let id = match (entity.get("TileID"), entity.get("Tile")) {
+        (Some(Value::Int(id)), _) => *id as i16,
+        (_, Some(Value::Byte(id))) => *id as u8 as i16,
+        _ => return Ok(()),
+    };
+
+    if let Some(name) = ids::old_to_new(id) {
+        entity.remove("TileID");
+        entity.remove("Tile");
+        entity.insert("Block".into(), Value::String(name.into()));
+    }
+
+    Ok(())
+}
+
+/// Every item stack an entity, tile entity or player can hold.
+fn upgrade_item_stacks_1_8(
+    _id: &str,
+    compound: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    for key in ["Item", "Potion", "FireworksItem", "RecordItem"] {
+        if let Some(item_stack) = compound.get_mut(key).and_then(NbtValueExt::as_compound) {
+            upgrade_item_stack(item_stack);
+        }
+    }
+
+    for key in ["Items", "Equipment", "Inventory", "EnderItems"] {
+        if let Some(item_stacks) = compound.get_mut(key).and_then(NbtValueExt::as_list) {
+            for item_stack in item_stacks {
+                if let Some(item_stack) = item_stack.as_compound() {
+                    upgrade_item_stack(item_stack);
+                }
+            }
+        }
+    }
+
+    let recipes = compound
+        .get_mut("Offers")
+        .and_then(NbtValueExt::as_compound)
+        .and_then(|offers| offers.get_mut("Recipes"))
+        .and_then(NbtValueExt::as_list);
+    if let Some(recipes) = recipes {
+        for recipe in recipes {
+            let recipe = recipe
+                .as_compound()
+                .ok_or_eyre("A recipe isn't a compound.")?;
+
+            for key in ["buy", "sell", "buyB"] {
+                if let Some(item_stack) = recipe.get_mut(key).and_then(NbtValueExt::as_compound) {
+                    upgrade_item_stack(item_stack);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lines become JSON strings, apart from ones that already are a JSON object or array.
+fn upgrade_sign_1_8(
+    _id: &str,
+    tile_entity: &mut HashMap<String, Value>,
+    _ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    for text_key in ["Text1", "Text2", "Text3", "Text4"] {
+        let text = tile_entity
+            .get(text_key)
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let text = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Object(_) | serde_json::Value::Array(_)) => text.to_string(),
+            _ => serde_json::Value::String(text.to_string()).to_string(),
+        };
+
+        tile_entity.insert(text_key.into(), Value::String(text));
+    }
+
+    Ok(())
+}
+
+/// Turns a numeric id into a namespaced one. Ids missing from [`ids::old_to_new`] stay
+/// numeric, which 1.8 still reads.
+pub fn upgrade_item_stack(item_stack: &mut HashMap<String, Value>) {
+    if let Some(Value::Short(id)) = item_stack.get("id") {
+        if let Some(name) = ids::old_to_new(*id) {
+            item_stack.insert("id".into(), Value::String(name.into()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ItemMap;
+    use crate::unknown::UnknownIds;
+    use crate::version::MinecraftVersion::*;
+
+    type Fix = fn(&str, &mut HashMap<String, Value>, &mut FixContext) -> color_eyre::Result<()>;
+
+    /// Downgrades a copy of `entity` and upgrades it again, which should give `entity` back.
+    fn round_trip(
+        downgrade: Fix,
+        upgrade: Fix,
+        id: &str,
+        entity: &HashMap<String, Value>,
+    ) -> HashMap<String, Value> {
+        let mut ctx = FixContext::new(V1_4_7);
+        let mut downgraded = entity.clone();
+        downgrade(id, &mut downgraded, &mut ctx).unwrap();
+        assert_ne!(&downgraded, entity);
+
+        let mut upgraded = downgraded.clone();
+        let id = match downgraded.get("id") {
+            Some(Value::String(id)) => id,
+            _ => id,
+        };
+        upgrade(id, &mut upgraded, &mut ctx).unwrap();
+        assert_eq!(&upgraded, entity);
+
+        downgraded
+    }
+
+    #[test]
+    fn hanging_facing_round_trips() {
+        // the downgraded tile is the block the frame hangs on
+        for (facing, tile_x, tile_z) in [(0, 10, 19), (1, 11, 20), (2, 10, 21), (3, 9, 20)] {
+            let frame = HashMap::from([
+                ("id".to_string(), Value::String("ItemFrame".into())),
+                ("Facing".to_string(), Value::Byte(facing)),
+                ("TileX".to_string(), Value::Int(10)),
+                ("TileY".to_string(), Value::Int(64)),
+                ("TileZ".to_string(), Value::Int(20)),
+                ("ItemRotation".to_string(), Value::Byte(4)),
+            ]);
+
+            let downgraded = round_trip(
+                hanging_facing_1_8,
+                upgrade_hanging_facing_1_8,
+                "ItemFrame",
+                &frame,
+            );
+            assert_eq!(downgraded.get("Facing"), None);
+            assert_eq!(downgraded["Direction"], Value::Byte(facing));
+            assert_eq!(downgraded["TileX"], Value::Int(tile_x));
+            assert_eq!(downgraded["TileZ"], Value::Int(tile_z));
+            assert_eq!(downgraded["ItemRotation"], Value::Byte(2));
+        }
+    }
+
+    #[test]
+    fn minecart_type_round_trips() {
+        for (id, minecart_type) in [
+            ("MinecartRideable", 0),
+            ("MinecartChest", 1),
+            ("MinecartFurnace", 2),
+        ] {
+            let minecart = HashMap::from([
+                ("id".to_string(), Value::String(id.into())),
+                ("Pos".to_string(), Value::List(vec![Value::Double(0.5)])),
+            ]);
+
+            let downgraded = round_trip(minecart_1_5, upgrade_minecart_1_5, id, &minecart);
+            assert_eq!(downgraded["id"], Value::String("Minecart".into()));
+            assert_eq!(downgraded["Type"], Value::Int(minecart_type));
+        }
+    }
+
+    #[test]
+    fn upgraded_signs_keep_json_components() {
+        let mut sign = HashMap::from([
+            ("Text1".to_string(), Value::String("hello".into())),
+            (
+                "Text2".to_string(),
+                Value::String(r#"{"text":"hi"}"#.into()),
+            ),
+            ("Text3".to_string(), Value::String(r#"["a","b"]"#.into())),
+            ("Text4".to_string(), Value::String("5".into())),
+        ]);
+        upgrade_sign_1_8("Sign", &mut sign, &mut FixContext::new(V1_8)).unwrap();
+
+        assert_eq!(sign["Text1"], Value::String(r#""hello""#.into()));
+        assert_eq!(sign["Text2"], Value::String(r#"{"text":"hi"}"#.into()));
+        assert_eq!(sign["Text3"], Value::String(r#"["a","b"]"#.into()));
+        assert_eq!(sign["Text4"], Value::String(r#""5""#.into()));
+    }
+
+    #[test]
+    fn falling_blocks_keep_ids_past_a_byte() {
+        let mut ctx = FixContext::new(V1_7_10);
+        let mut entity = HashMap::from([
+            ("id".to_string(), Value::String("FallingSand".into())),
+            ("Block".to_string(), Value::String("minecraft:slime".into())),
+            ("Data".to_string(), Value::Byte(0)),
+        ]);
+        falling_sand_1_8("FallingSand", &mut entity, &mut ctx).unwrap();
+        assert_eq!(entity["Tile"], Value::Byte(165u8 as i8));
+        assert_eq!(entity["TileID"], Value::Int(165));
+
+        // slime blocks are lime wool on 1.7
+        ctx.blocks.downgrade_entity(&mut entity);
+        assert_eq!(entity["TileID"], Value::Int(35));
+        assert_eq!(entity["Tile"], Value::Byte(35));
+        assert_eq!(entity["Data"], Value::Byte(5));
+
+        // without the int the byte is still read unsigned
+        entity.remove("TileID");
+        entity.insert("Tile".to_string(), Value::Byte(165u8 as i8));
+        entity.insert("Data".to_string(), Value::Byte(0));
+        ctx.blocks.downgrade_entity(&mut entity);
+        assert_eq!(entity["Tile"], Value::Byte(35));
+    }
+
+    #[test]
+    fn sign_lines_lose_codes_before_text() {
+        let downgrade = |text: &str| {
+            let mut sign = HashMap::from([("Text1".to_string(), Value::String(text.into()))]);
+            downgrade_sign_text(&mut sign, "Text1").unwrap();
+            sign["Text1"].as_str().unwrap().to_string()
+        };
+
+        // a short coloured line keeps its codes
+        assert_eq!(downgrade(r#"{"text":"Hi","color":"red"}"#), "§cHi");
+        // 14 visible characters fit, but not with the codes
+        assert_eq!(
+            downgrade(r#"{"text":"Welcome, hero!","color":"red","bold":true}"#),
+            "Welcome, hero!"
+        );
+        assert_eq!(downgrade(r#""Sixteen letters!""#), "Sixteen letters");
+    }
+
+    #[test]
+    fn tameable_owners_are_looked_up_normalized() {
+        let mut ctx = FixContext::new(V1_7_2);
+        ctx.player_names.insert(
+            "069a79f4-44e9-4726-a5be-fca90e38aaf5".to_string(),
+            "Notch".to_string(),
+        );
+
+        let mut wolf = HashMap::from([(
+            "OwnerUUID".to_string(),
+            Value::String("069A79F444E94726A5BEFCA90E38AAF5".into()),
+        )]);
+        tameable_owner_1_7_6("Wolf", &mut wolf, &mut ctx).unwrap();
+
+        assert_eq!(wolf.get("OwnerUUID"), None);
+        assert_eq!(wolf["Owner"], Value::String("Notch".into()));
+    }
+
+    #[test]
+    fn upgraded_level_data_gets_the_new_defaults() {
+        let mut data = HashMap::from([
+            ("clearWeatherTime".to_string(), Value::Int(100)),
+            (
+                "GameRules".to_string(),
+                Value::Compound(HashMap::from([(
+                    "doDaylightCycle".to_string(),
+                    Value::String("false".into()),
+                )])),
+            ),
+        ]);
+        let mut ctx = FixContext::new(V1_8);
+        upgrade_level_data_1_6("", &mut data, &mut ctx).unwrap();
+        upgrade_level_data_1_8("", &mut data, &mut ctx).unwrap();
+
+        assert_eq!(data["BorderSize"], Value::Double(60_000_000.0));
+        // what's there already is kept
+        assert_eq!(data["clearWeatherTime"], Value::Int(100));
+        let Value::Compound(game_rules) = &data["GameRules"] else {
+            panic!("GameRules isn't a compound.");
+        };
+        assert_eq!(game_rules["doDaylightCycle"], Value::String("false".into()));
+        assert_eq!(game_rules["randomTickSpeed"], Value::String("3".into()));
+    }
+
+    #[test]
+    fn replaced_items_lose_their_damage() {
+        let mut ctx = FixContext::new(V1_7_10);
+        ctx.items = ItemMap::new(V1_7_10, &[]);
+        ctx.unknown_ids = UnknownIds::new("replace:minecraft:diamond".parse().unwrap());
+
+        for id in ["minecraft:prismarine_shard", "mod:thing"] {
+            let mut item_stack = HashMap::from([
+                ("id".to_string(), Value::String(id.into())),
+                ("Damage".to_string(), Value::Short(4)),
+            ]);
+            downgrade_item_stack(&mut item_stack, &mut ctx).unwrap();
+
+            assert_eq!(item_stack["id"], Value::Short(264), "{id}");
+            assert_eq!(item_stack["Damage"], Value::Short(0), "{id}");
+        }
+    }
+}
@@ -1,323 +1,836 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::version::MinecraftVersion;
+
+/// Namespaced id, numeric id and the version that introduced it, for every block and item.
+/// The one table all the id lookups are built from. Blocks and items that share a name but
+/// not a number, like `minecraft:bed`, have a row each.
+const IDS: &[(&str, i16, MinecraftVersion)] = {
+    use MinecraftVersion::*;
+
+    &[
+        ("minecraft:air", 0, V1_4_5),
+        ("minecraft:stone", 1, V1_4_5),
+        ("minecraft:grass", 2, V1_4_5),
+        ("minecraft:dirt", 3, V1_4_5),
+        ("minecraft:cobblestone", 4, V1_4_5),
+        ("minecraft:planks", 5, V1_4_5),
+        ("minecraft:sapling", 6, V1_4_5),
+        ("minecraft:bedrock", 7, V1_4_5),
+        ("minecraft:flowing_water", 8, V1_4_5),
+        ("minecraft:water", 9, V1_4_5),
+        ("minecraft:flowing_lava", 10, V1_4_5),
+        ("minecraft:lava", 11, V1_4_5),
+        ("minecraft:sand", 12, V1_4_5),
+        ("minecraft:gravel", 13, V1_4_5),
+        ("minecraft:gold_ore", 14, V1_4_5),
+        ("minecraft:iron_ore", 15, V1_4_5),
+        ("minecraft:coal_ore", 16, V1_4_5),
+        ("minecraft:log", 17, V1_4_5),
+        ("minecraft:leaves", 18, V1_4_5),
+        ("minecraft:sponge", 19, V1_4_5),
+        ("minecraft:glass", 20, V1_4_5),
+        ("minecraft:lapis_ore", 21, V1_4_5),
+        ("minecraft:lapis_block", 22, V1_4_5),
+        ("minecraft:dispenser", 23, V1_4_5),
+        ("minecraft:sandstone", 24, V1_4_5),
+        ("minecraft:noteblock", 25, V1_4_5),
+        ("minecraft:bed", 26, V1_4_5),
+        ("minecraft:golden_rail", 27, V1_4_5),
+        ("minecraft:detector_rail", 28, V1_4_5),
+        ("minecraft:sticky_piston", 29, V1_4_5),
+        ("minecraft:web", 30, V1_4_5),
+        ("minecraft:tallgrass", 31, V1_4_5),
+        ("minecraft:deadbush", 32, V1_4_5),
+        ("minecraft:piston", 33, V1_4_5),
+        ("minecraft:piston_head", 34, V1_4_5),
+        ("minecraft:wool", 35, V1_4_5),
+        ("minecraft:piston_extension", 36, V1_4_5),
+        ("minecraft:yellow_flower", 37, V1_4_5),
+        ("minecraft:red_flower", 38, V1_4_5),
+        ("minecraft:brown_mushroom", 39, V1_4_5),
+        ("minecraft:red_mushroom", 40, V1_4_5),
+        ("minecraft:gold_block", 41, V1_4_5),
+        ("minecraft:iron_block", 42, V1_4_5),
+        ("minecraft:double_stone_slab", 43, V1_4_5),
+        ("minecraft:stone_slab", 44, V1_4_5),
+        ("minecraft:brick_block", 45, V1_4_5),
+        ("minecraft:tnt", 46, V1_4_5),
+        ("minecraft:bookshelf", 47, V1_4_5),
+        ("minecraft:mossy_cobblestone", 48, V1_4_5),
+        ("minecraft:obsidian", 49, V1_4_5),
+        ("minecraft:torch", 50, V1_4_5),
+        ("minecraft:fire", 51, V1_4_5),
+        ("minecraft:mob_spawner", 52, V1_4_5),
+        ("minecraft:oak_stairs", 53, V1_4_5),
+        ("minecraft:chest", 54, V1_4_5),
+        ("minecraft:redstone_wire", 55, V1_4_5),
+        ("minecraft:diamond_ore", 56, V1_4_5),
+        ("minecraft:diamond_block", 57, V1_4_5),
+        ("minecraft:crafting_table", 58, V1_4_5),
+        ("minecraft:wheat", 59, V1_4_5),
+        ("minecraft:farmland", 60, V1_4_5),
+        ("minecraft:furnace", 61, V1_4_5),
+        ("minecraft:lit_furnace", 62, V1_4_5),
+        ("minecraft:standing_sign", 63, V1_4_5),
+        ("minecraft:wooden_door", 64, V1_4_5),
+        ("minecraft:ladder", 65, V1_4_5),
+        ("minecraft:rail", 66, V1_4_5),
+        ("minecraft:stone_stairs", 67, V1_4_5),
+        ("minecraft:wall_sign", 68, V1_4_5),
+        ("minecraft:lever", 69, V1_4_5),
+        ("minecraft:stone_pressure_plate", 70, V1_4_5),
+        ("minecraft:iron_door", 71, V1_4_5),
+        ("minecraft:wooden_pressure_plate", 72, V1_4_5),
+        ("minecraft:redstone_ore", 73, V1_4_5),
+        ("minecraft:lit_redstone_ore", 74, V1_4_5),
+        ("minecraft:unlit_redstone_torch", 75, V1_4_5),
+        ("minecraft:redstone_torch", 76, V1_4_5),
+        ("minecraft:stone_button", 77, V1_4_5),
+        ("minecraft:snow_layer", 78, V1_4_5),
+        ("minecraft:ice", 79, V1_4_5),
+        ("minecraft:snow", 80, V1_4_5),
+        ("minecraft:cactus", 81, V1_4_5),
+        ("minecraft:clay", 82, V1_4_5),
+        ("minecraft:reeds", 83, V1_4_5),
+        ("minecraft:jukebox", 84, V1_4_5),
+        ("minecraft:fence", 85, V1_4_5),
+        ("minecraft:pumpkin", 86, V1_4_5),
+        ("minecraft:netherrack", 87, V1_4_5),
+        ("minecraft:soul_sand", 88, V1_4_5),
+        ("minecraft:glowstone", 89, V1_4_5),
+        ("minecraft:portal", 90, V1_4_5),
+        ("minecraft:lit_pumpkin", 91, V1_4_5),
+        ("minecraft:cake", 92, V1_4_5),
+        ("minecraft:unpowered_repeater", 93, V1_4_5),
+        ("minecraft:powered_repeater", 94, V1_4_5),
+        ("minecraft:stained_glass", 95, V1_7),
+        ("minecraft:trapdoor", 96, V1_4_5),
+        ("minecraft:monster_egg", 97, V1_4_5),
+        ("minecraft:stonebrick", 98, V1_4_5),
+        ("minecraft:brown_mushroom_block", 99, V1_4_5),
+        ("minecraft:red_mushroom_block", 100, V1_4_5),
+        ("minecraft:iron_bars", 101, V1_4_5),
+        ("minecraft:glass_pane", 102, V1_4_5),
+        ("minecraft:melon_block", 103, V1_4_5),
+        ("minecraft:pumpkin_stem", 104, V1_4_5),
+        ("minecraft:melon_stem", 105, V1_4_5),
+        ("minecraft:vine", 106, V1_4_5),
+        ("minecraft:fence_gate", 107, V1_4_5),
+        ("minecraft:brick_stairs", 108, V1_4_5),
+        ("minecraft:stone_brick_stairs", 109, V1_4_5),
+        ("minecraft:mycelium", 110, V1_4_5),
+        ("minecraft:waterlily", 111, V1_4_5),
+        ("minecraft:nether_brick", 112, V1_4_5),
+        ("minecraft:nether_brick_fence", 113, V1_4_5),
+        ("minecraft:nether_brick_stairs", 114, V1_4_5),
+        ("minecraft:nether_wart", 115, V1_4_5),
+        ("minecraft:enchanting_table", 116, V1_4_5),
+        ("minecraft:brewing_stand", 117, V1_4_5),
+        ("minecraft:cauldron", 118, V1_4_5),
+        ("minecraft:end_portal", 119, V1_4_5),
+        ("minecraft:end_portal_frame", 120, V1_4_5),
+        ("minecraft:end_stone", 121, V1_4_5),
+        ("minecraft:dragon_egg", 122, V1_4_5),
+        ("minecraft:redstone_lamp", 123, V1_4_5),
+        ("minecraft:lit_redstone_lamp", 124, V1_4_5),
+        ("minecraft:double_wooden_slab", 125, V1_4_5),
+        ("minecraft:wooden_slab", 126, V1_4_5),
+        ("minecraft:cocoa", 127, V1_4_5),
+        ("minecraft:sandstone_stairs", 128, V1_4_5),
+        ("minecraft:emerald_ore", 129, V1_4_5),
+        ("minecraft:ender_chest", 130, V1_4_5),
+        ("minecraft:tripwire_hook", 131, V1_4_5),
+        ("minecraft:tripwire", 132, V1_4_5),
+        ("minecraft:emerald_block", 133, V1_4_5),
+        ("minecraft:spruce_stairs", 134, V1_4_5),
+        ("minecraft:birch_stairs", 135, V1_4_5),
+        ("minecraft:jungle_stairs", 136, V1_4_5),
+        ("minecraft:command_block", 137, V1_4_5),
+        ("minecraft:beacon", 138, V1_4_5),
+        ("minecraft:cobblestone_wall", 139, V1_4_5),
+        ("minecraft:flower_pot", 140, V1_4_5),
+        ("minecraft:carrots", 141, V1_4_5),
+        ("minecraft:potatoes", 142, V1_4_5),
+        ("minecraft:wooden_button", 143, V1_4_5),
+        ("minecraft:skull", 144, V1_4_5),
+        ("minecraft:anvil", 145, V1_4_5),
+        ("minecraft:trapped_chest", 146, V1_5),
+        ("minecraft:light_weighted_pressure_plate", 147, V1_5),
+        ("minecraft:heavy_weighted_pressure_plate", 148, V1_5),
+        ("minecraft:unpowered_comparator", 149, V1_5),
+        ("minecraft:powered_comparator", 150, V1_5),
+        ("minecraft:daylight_detector", 151, V1_5),
+        ("minecraft:redstone_block", 152, V1_5),
+        ("minecraft:quartz_ore", 153, V1_5),
+        ("minecraft:hopper", 154, V1_5),
+        ("minecraft:quartz_block", 155, V1_5),
+        ("minecraft:quartz_stairs", 156, V1_5),
+        ("minecraft:activator_rail", 157, V1_5),
+        ("minecraft:dropper", 158, V1_5),
+        ("minecraft:stained_hardened_clay", 159, V1_6),
+        ("minecraft:stained_glass_pane", 160, V1_7),
+        ("minecraft:leaves2", 161, V1_7),
+        ("minecraft:log2", 162, V1_7),
+        ("minecraft:acacia_stairs", 163, V1_7),
+        ("minecraft:dark_oak_stairs", 164, V1_7),
+        ("minecraft:slime", 165, V1_8),
+        ("minecraft:barrier", 166, V1_8),
+        ("minecraft:iron_trapdoor", 167, V1_8),
+        ("minecraft:prismarine", 168, V1_8),
+        ("minecraft:sea_lantern", 169, V1_8),
+        ("minecraft:hay_block", 170, V1_6),
+        ("minecraft:carpet", 171, V1_6),
+        ("minecraft:hardened_clay", 172, V1_6),
+        ("minecraft:coal_block", 173, V1_6),
+        ("minecraft:packed_ice", 174, V1_7),
+        ("minecraft:double_plant", 175, V1_7),
+        ("minecraft:standing_banner", 176, V1_8),
+        ("minecraft:wall_banner", 177, V1_8),
+        ("minecraft:daylight_detector_inverted", 178, V1_8),
+        ("minecraft:red_sandstone", 179, V1_8),
+        ("minecraft:red_sandstone_stairs", 180, V1_8),
+        ("minecraft:double_stone_slab2", 181, V1_8),
+        ("minecraft:stone_slab2", 182, V1_8),
+        ("minecraft:spruce_fence_gate", 183, V1_8),
+        ("minecraft:birch_fence_gate", 184, V1_8),
+        ("minecraft:jungle_fence_gate", 185, V1_8),
+        ("minecraft:dark_oak_fence_gate", 186, V1_8),
+        ("minecraft:acacia_fence_gate", 187, V1_8),
+        ("minecraft:spruce_fence", 188, V1_8),
+        ("minecraft:birch_fence", 189, V1_8),
+        ("minecraft:jungle_fence", 190, V1_8),
+        ("minecraft:dark_oak_fence", 191, V1_8),
+        ("minecraft:acacia_fence", 192, V1_8),
+        ("minecraft:spruce_door", 193, V1_8),
+        ("minecraft:birch_door", 194, V1_8),
+        ("minecraft:jungle_door", 195, V1_8),
+        ("minecraft:acacia_door", 196, V1_8),
+        ("minecraft:dark_oak_door", 197, V1_8),
+        ("minecraft:end_rod", 198, V1_9_4),
+        ("minecraft:chorus_plant", 199, V1_9_4),
+        ("minecraft:chorus_flower", 200, V1_9_4),
+        ("minecraft:purpur_block", 201, V1_9_4),
+        ("minecraft:purpur_pillar", 202, V1_9_4),
+        ("minecraft:purpur_stairs", 203, V1_9_4),
+        ("minecraft:purpur_double_slab", 204, V1_9_4),
+        ("minecraft:purpur_slab", 205, V1_9_4),
+        ("minecraft:end_bricks", 206, V1_9_4),
+        ("minecraft:beetroots", 207, V1_9_4),
+        ("minecraft:grass_path", 208, V1_9_4),
+        ("minecraft:end_gateway", 209, V1_9_4),
+        ("minecraft:repeating_command_block", 210, V1_9_4),
+        ("minecraft:chain_command_block", 211, V1_9_4),
+        ("minecraft:frosted_ice", 212, V1_9_4),
+        ("minecraft:structure_block", 255, V1_9_4),
+        ("minecraft:iron_shovel", 256, V1_4_5),
+        ("minecraft:iron_pickaxe", 257, V1_4_5),
+        ("minecraft:iron_axe", 258, V1_4_5),
+        ("minecraft:flint_and_steel", 259, V1_4_5),
+        ("minecraft:apple", 260, V1_4_5),
+        ("minecraft:bow", 261, V1_4_5),
+        ("minecraft:arrow", 262, V1_4_5),
+        ("minecraft:coal", 263, V1_4_5),
+        ("minecraft:diamond", 264, V1_4_5),
+        ("minecraft:iron_ingot", 265, V1_4_5),
+        ("minecraft:gold_ingot", 266, V1_4_5),
+        ("minecraft:iron_sword", 267, V1_4_5),
+        ("minecraft:wooden_sword", 268, V1_4_5),
+        ("minecraft:wooden_shovel", 269, V1_4_5),
+        ("minecraft:wooden_pickaxe", 270, V1_4_5),
+        ("minecraft:wooden_axe", 271, V1_4_5),
+        ("minecraft:stone_sword", 272, V1_4_5),
+        ("minecraft:stone_shovel", 273, V1_4_5),
+        ("minecraft:stone_pickaxe", 274, V1_4_5),
+        ("minecraft:stone_axe", 275, V1_4_5),
+        ("minecraft:diamond_sword", 276, V1_4_5),
+        ("minecraft:diamond_shovel", 277, V1_4_5),
+        ("minecraft:diamond_pickaxe", 278, V1_4_5),
+        ("minecraft:diamond_axe", 279, V1_4_5),
+        ("minecraft:stick", 280, V1_4_5),
+        ("minecraft:bowl", 281, V1_4_5),
+        ("minecraft:mushroom_stew", 282, V1_4_5),
+        ("minecraft:golden_sword", 283, V1_4_5),
+        ("minecraft:golden_shovel", 284, V1_4_5),
+        ("minecraft:golden_pickaxe", 285, V1_4_5),
+        ("minecraft:golden_axe", 286, V1_4_5),
+        ("minecraft:string", 287, V1_4_5),
+        ("minecraft:feather", 288, V1_4_5),
+        ("minecraft:gunpowder", 289, V1_4_5),
+        ("minecraft:wooden_hoe", 290, V1_4_5),
+        ("minecraft:stone_hoe", 291, V1_4_5),
+        ("minecraft:iron_hoe", 292, V1_4_5),
+        ("minecraft:diamond_hoe", 293, V1_4_5),
+        ("minecraft:golden_hoe", 294, V1_4_5),
+        ("minecraft:wheat_seeds", 295, V1_4_5),
+        ("minecraft:wheat", 296, V1_4_5),
+        ("minecraft:bread", 297, V1_4_5),
+        ("minecraft:leather_helmet", 298, V1_4_5),
+        ("minecraft:leather_chestplate", 299, V1_4_5),
+        ("minecraft:leather_leggings", 300, V1_4_5),
+        ("minecraft:leather_boots", 301, V1_4_5),
+        ("minecraft:chainmail_helmet", 302, V1_4_5),
+        ("minecraft:chainmail_chestplate", 303, V1_4_5),
+        ("minecraft:chainmail_leggings", 304, V1_4_5),
+        ("minecraft:chainmail_boots", 305, V1_4_5),
+        ("minecraft:iron_helmet", 306, V1_4_5),
+        ("minecraft:iron_chestplate", 307, V1_4_5),
+        ("minecraft:iron_leggings", 308, V1_4_5),
+        ("minecraft:iron_boots", 309, V1_4_5),
+        ("minecraft:diamond_helmet", 310, V1_4_5),
+        ("minecraft:diamond_chestplate", 311, V1_4_5),
+        ("minecraft:diamond_leggings", 312, V1_4_5),
+        ("minecraft:diamond_boots", 313, V1_4_5),
+        ("minecraft:golden_helmet", 314, V1_4_5),
+        ("minecraft:golden_chestplate", 315, V1_4_5),
+        ("minecraft:golden_leggings", 316, V1_4_5),
+        ("minecraft:golden_boots", 317, V1_4_5),
+        ("minecraft:flint", 318, V1_4_5),
+        ("minecraft:porkchop", 319, V1_4_5),
+        ("minecraft:cooked_porkchop", 320, V1_4_5),
+        ("minecraft:painting", 321, V1_4_5),
+        ("minecraft:golden_apple", 322, V1_4_5),
+        ("minecraft:sign", 323, V1_4_5),
+        ("minecraft:wooden_door", 324, V1_4_5),
+        ("minecraft:bucket", 325, V1_4_5),
+        ("minecraft:water_bucket", 326, V1_4_5),
+        ("minecraft:lava_bucket", 327, V1_4_5),
+        ("minecraft:minecart", 328, V1_4_5),
+        ("minecraft:saddle", 329, V1_4_5),
+        ("minecraft:iron_door", 330, V1_4_5),
+        ("minecraft:redstone", 331, V1_4_5),
+        ("minecraft:snowball", 332, V1_4_5),
+        ("minecraft:boat", 333, V1_4_5),
+        ("minecraft:leather", 334, V1_4_5),
+        ("minecraft:milk_bucket", 335, V1_4_5),
+        ("minecraft:brick", 336, V1_4_5),
+        ("minecraft:clay_ball", 337, V1_4_5),
+        ("minecraft:reeds", 338, V1_4_5),
+        ("minecraft:paper", 339, V1_4_5),
+        ("minecraft:book", 340, V1_4_5),
+        ("minecraft:slime_ball", 341, V1_4_5),
+        ("minecraft:chest_minecart", 342, V1_4_5),
+        ("minecraft:furnace_minecart", 343, V1_4_5),
+        ("minecraft:egg", 344, V1_4_5),
+        ("minecraft:compass", 345, V1_4_5),
+        ("minecraft:fishing_rod", 346, V1_4_5),
+        ("minecraft:clock", 347, V1_4_5),
+        ("minecraft:glowstone_dust", 348, V1_4_5),
+        ("minecraft:fish", 349, V1_4_5),
+        ("minecraft:cooked_fish", 350, V1_4_5),
+        ("minecraft:dye", 351, V1_4_5),
+        ("minecraft:bone", 352, V1_4_5),
+        ("minecraft:sugar", 353, V1_4_5),
+        ("minecraft:cake", 354, V1_4_5),
+        ("minecraft:bed", 355, V1_4_5),
+        ("minecraft:repeater", 356, V1_4_5),
+        ("minecraft:cookie", 357, V1_4_5),
+        ("minecraft:filled_map", 358, V1_4_5),
+        ("minecraft:shears", 359, V1_4_5),
+        ("minecraft:melon", 360, V1_4_5),
+        ("minecraft:pumpkin_seeds", 361, V1_4_5),
+        ("minecraft:melon_seeds", 362, V1_4_5),
+        ("minecraft:beef", 363, V1_4_5),
+        ("minecraft:cooked_beef", 364, V1_4_5),
+        ("minecraft:chicken", 365, V1_4_5),
+        ("minecraft:cooked_chicken", 366, V1_4_5),
+        ("minecraft:rotten_flesh", 367, V1_4_5),
+        ("minecraft:ender_pearl", 368, V1_4_5),
+        ("minecraft:blaze_rod", 369, V1_4_5),
+        ("minecraft:ghast_tear", 370, V1_4_5),
+        ("minecraft:gold_nugget", 371, V1_4_5),
+        ("minecraft:nether_wart", 372, V1_4_5),
+        ("minecraft:potion", 373, V1_4_5),
+        ("minecraft:glass_bottle", 374, V1_4_5),
+        ("minecraft:spider_eye", 375, V1_4_5),
+        ("minecraft:fermented_spider_eye", 376, V1_4_5),
+        ("minecraft:blaze_powder", 377, V1_4_5),
+        ("minecraft:magma_cream", 378, V1_4_5),
+        ("minecraft:brewing_stand", 379, V1_4_5),
+        ("minecraft:cauldron", 380, V1_4_5),
+        ("minecraft:ender_eye", 381, V1_4_5),
+        ("minecraft:speckled_melon", 382, V1_4_5),
+        ("minecraft:spawn_egg", 383, V1_4_5),
+        ("minecraft:experience_bottle", 384, V1_4_5),
+        ("minecraft:fire_charge", 385, V1_4_5),
+        ("minecraft:writable_book", 386, V1_4_5),
+        ("minecraft:written_book", 387, V1_4_5),
+        ("minecraft:emerald", 388, V1_4_5),
+        ("minecraft:item_frame", 389, V1_4_5),
+        ("minecraft:flower_pot", 390, V1_4_5),
+        ("minecraft:carrot", 391, V1_4_5),
+        ("minecraft:potato", 392, V1_4_5),
+        ("minecraft:baked_potato", 393, V1_4_5),
+        ("minecraft:poisonous_potato", 394, V1_4_5),
+        ("minecraft:map", 395, V1_4_5),
+        ("minecraft:golden_carrot", 396, V1_4_5),
+        ("minecraft:skull", 397, V1_4_5),
+        ("minecraft:carrot_on_a_stick", 398, V1_4_5),
+        ("minecraft:nether_star", 399, V1_4_5),
+        ("minecraft:pumpkin_pie", 400, V1_4_5),
+        ("minecraft:fireworks", 401, V1_4_6),
+        ("minecraft:firework_charge", 402, V1_4_6),
+        ("minecraft:enchanted_book", 403, V1_4_6),
+        ("minecraft:comparator", 404, V1_5),
+        ("minecraft:netherbrick", 405, V1_5),
+        ("minecraft:quartz", 406, V1_5),
+        ("minecraft:tnt_minecart", 407, V1_5),
+        ("minecraft:hopper_minecart", 408, V1_5),
+        ("minecraft:prismarine_shard", 409, V1_8),
+        ("minecraft:prismarine_crystals", 410, V1_8),
+        ("minecraft:rabbit", 411, V1_8),
+        ("minecraft:cooked_rabbit", 412, V1_8),
+        ("minecraft:rabbit_stew", 413, V1_8),
+        ("minecraft:rabbit_foot", 414, V1_8),
+        ("minecraft:rabbit_hide", 415, V1_8),
+        ("minecraft:armor_stand", 416, V1_8),
+        ("minecraft:iron_horse_armor", 417, V1_6),
+        ("minecraft:golden_horse_armor", 418, V1_6),
+        ("minecraft:diamond_horse_armor", 419, V1_6),
+        ("minecraft:lead", 420, V1_6),
+        ("minecraft:name_tag", 421, V1_6),
+        ("minecraft:command_block_minecart", 422, V1_7),
+        ("minecraft:mutton", 423, V1_8),
+        ("minecraft:cooked_mutton", 424, V1_8),
+        ("minecraft:banner", 425, V1_8),
+        ("minecraft:end_crystal", 426, V1_9_4),
+        ("minecraft:spruce_door", 427, V1_8),
+        ("minecraft:birch_door", 428, V1_8),
+        ("minecraft:jungle_door", 429, V1_8),
+        ("minecraft:acacia_door", 430, V1_8),
+        ("minecraft:dark_oak_door", 431, V1_8),
+        ("minecraft:chorus_fruit", 432, V1_9_4),
+        ("minecraft:chorus_fruit_popped", 433, V1_9_4),
+        ("minecraft:beetroot", 434, V1_9_4),
+        ("minecraft:beetroot_seeds", 435, V1_9_4),
+        ("minecraft:beetroot_soup", 436, V1_9_4),
+        ("minecraft:dragon_breath", 437, V1_9_4),
+        ("minecraft:splash_potion", 438, V1_9_4),
+        ("minecraft:spectral_arrow", 439, V1_9_4),
+        ("minecraft:tipped_arrow", 440, V1_9_4),
+        ("minecraft:lingering_potion", 441, V1_9_4),
+        ("minecraft:shield", 442, V1_9_4),
+        ("minecraft:elytra", 443, V1_9_4),
+        ("minecraft:spruce_boat", 444, V1_9_4),
+        ("minecraft:birch_boat", 445, V1_9_4),
+        ("minecraft:jungle_boat", 446, V1_9_4),
+        ("minecraft:acacia_boat", 447, V1_9_4),
+        ("minecraft:dark_oak_boat", 448, V1_9_4),
+        ("minecraft:record_13", 2256, V1_4_5),
+        ("minecraft:record_cat", 2257, V1_4_5),
+        ("minecraft:record_blocks", 2258, V1_4_5),
+        ("minecraft:record_chirp", 2259, V1_4_5),
+        ("minecraft:record_far", 2260, V1_4_5),
+        ("minecraft:record_mall", 2261, V1_4_5),
+        ("minecraft:record_mellohi", 2262, V1_4_5),
+        ("minecraft:record_stal", 2263, V1_4_5),
+        ("minecraft:record_strad", 2264, V1_4_5),
+        ("minecraft:record_ward", 2265, V1_4_5),
+        ("minecraft:record_11", 2266, V1_4_5),
+        ("minecraft:record_wait", 2267, V1_4_5),
+    ]
+};
+
+/// Readable name, numeric id and damage of the blocks and items that share an id and are
+/// told apart by their damage, like the wool colours. Names follow the ids they got once
+/// every variant had its own. The one table both variant lookups are built from.
+const VARIANTS: &[(&str, i16, i16)] = &[
+    // minecraft:stone
+    ("stone", 1, 0),
+    ("granite", 1, 1),
+    ("polished_granite", 1, 2),
+    ("diorite", 1, 3),
+    ("polished_diorite", 1, 4),
+    ("andesite", 1, 5),
+    ("polished_andesite", 1, 6),
+    // minecraft:dirt
+    ("dirt", 3, 0),
+    ("coarse_dirt", 3, 1),
+    ("podzol", 3, 2),
+    // minecraft:planks
+    ("oak_planks", 5, 0),
+    ("spruce_planks", 5, 1),
+    ("birch_planks", 5, 2),
+    ("jungle_planks", 5, 3),
+    ("acacia_planks", 5, 4),
+    ("dark_oak_planks", 5, 5),
+    // minecraft:sapling
+    ("oak_sapling", 6, 0),
+    ("spruce_sapling", 6, 1),
+    ("birch_sapling", 6, 2),
+    ("jungle_sapling", 6, 3),
+    ("acacia_sapling", 6, 4),
+    ("dark_oak_sapling", 6, 5),
+    // minecraft:sand
+    ("sand", 12, 0),
+    ("red_sand", 12, 1),
+    // minecraft:log
+    ("oak_log", 17, 0),
+    ("spruce_log", 17, 1),
+    ("birch_log", 17, 2),
+    ("jungle_log", 17, 3),
+    // minecraft:leaves
+    ("oak_leaves", 18, 0),
+    ("spruce_leaves", 18, 1),
+    ("birch_leaves", 18, 2),
+    ("jungle_leaves", 18, 3),
+    // minecraft:sponge
+    ("sponge", 19, 0),
+    ("wet_sponge", 19, 1),
+    // minecraft:sandstone
+    ("sandstone", 24, 0),
+    ("chiseled_sandstone", 24, 1),
+    ("smooth_sandstone", 24, 2),
+    // minecraft:tallgrass
+    ("short_grass", 31, 1),
+    ("fern", 31, 2),
+    // minecraft:wool
+    ("white_wool", 35, 0),
+    ("orange_wool", 35, 1),
+    ("magenta_wool", 35, 2),
+    ("light_blue_wool", 35, 3),
+    ("yellow_wool", 35, 4),
+    ("lime_wool", 35, 5),
+    ("pink_wool", 35, 6),
+    ("gray_wool", 35, 7),
+    ("light_gray_wool", 35, 8),
+    ("cyan_wool", 35, 9),
+    ("purple_wool", 35, 10),
+    ("blue_wool", 35, 11),
+    ("brown_wool", 35, 12),
+    ("green_wool", 35, 13),
+    ("red_wool", 35, 14),
+    ("black_wool", 35, 15),
+    // minecraft:red_flower
+    ("poppy", 38, 0),
+    ("blue_orchid", 38, 1),
+    ("allium", 38, 2),
+    ("azure_bluet", 38, 3),
+    ("red_tulip", 38, 4),
+    ("orange_tulip", 38, 5),
+    ("white_tulip", 38, 6),
+    ("pink_tulip", 38, 7),
+    ("oxeye_daisy", 38, 8),
+    // minecraft:stone_slab
+    ("stone_slab", 44, 0),
+    ("sandstone_slab", 44, 1),
+    ("petrified_oak_slab", 44, 2),
+    ("cobblestone_slab", 44, 3),
+    ("brick_slab", 44, 4),
+    ("stone_brick_slab", 44, 5),
+    ("nether_brick_slab", 44, 6),
+    ("quartz_slab", 44, 7),
+    // minecraft:stained_glass
+    ("white_stained_glass", 95, 0),
+    ("orange_stained_glass", 95, 1),
+    ("magenta_stained_glass", 95, 2),
+    ("light_blue_stained_glass", 95, 3),
+    ("yellow_stained_glass", 95, 4),
+    ("lime_stained_glass", 95, 5),
+    ("pink_stained_glass", 95, 6),
+    ("gray_stained_glass", 95, 7),
+    ("light_gray_stained_glass", 95, 8),
+    ("cyan_stained_glass", 95, 9),
+    ("purple_stained_glass", 95, 10),
+    ("blue_stained_glass", 95, 11),
+    ("brown_stained_glass", 95, 12),
+    ("green_stained_glass", 95, 13),
+    ("red_stained_glass", 95, 14),
+    ("black_stained_glass", 95, 15),
+    // minecraft:monster_egg
+    ("infested_stone", 97, 0),
+    ("infested_cobblestone", 97, 1),
+    ("infested_stone_bricks", 97, 2),
+    ("infested_mossy_stone_bricks", 97, 3),
+    ("infested_cracked_stone_bricks", 97, 4),
+    ("infested_chiseled_stone_bricks", 97, 5),
+    // minecraft:stonebrick
+    ("stone_bricks", 98, 0),
+    ("mossy_stone_bricks", 98, 1),
+    ("cracked_stone_bricks", 98, 2),
+    ("chiseled_stone_bricks", 98, 3),
+    // minecraft:wooden_slab
+    ("oak_slab", 126, 0),
+    ("spruce_slab", 126, 1),
+    ("birch_slab", 126, 2),
+    ("jungle_slab", 126, 3),
+    ("acacia_slab", 126, 4),
+    ("dark_oak_slab", 126, 5),
+    // minecraft:anvil
+    ("anvil", 145, 0),
+    ("chipped_anvil", 145, 1),
+    ("damaged_anvil", 145, 2),
+    // minecraft:quartz_block
+    ("quartz_block", 155, 0),
+    ("chiseled_quartz_block", 155, 1),
+    ("quartz_pillar", 155, 2),
+    // minecraft:stained_hardened_clay
+    ("white_terracotta", 159, 0),
+    ("orange_terracotta", 159, 1),
+    ("magenta_terracotta", 159, 2),
+    ("light_blue_terracotta", 159, 3),
+    ("yellow_terracotta", 159, 4),
+    ("lime_terracotta", 159, 5),
+    ("pink_terracotta", 159, 6),
+    ("gray_terracotta", 159, 7),
+    ("light_gray_terracotta", 159, 8),
+    ("cyan_terracotta", 159, 9),
+    ("purple_terracotta", 159, 10),
+    ("blue_terracotta", 159, 11),
+    ("brown_terracotta", 159, 12),
+    ("green_terracotta", 159, 13),
+    ("red_terracotta", 159, 14),
+    ("black_terracotta", 159, 15),
+    // minecraft:stained_glass_pane
+    ("white_stained_glass_pane", 160, 0),
+    ("orange_stained_glass_pane", 160, 1),
+    ("magenta_stained_glass_pane", 160, 2),
+    ("light_blue_stained_glass_pane", 160, 3),
+    ("yellow_stained_glass_pane", 160, 4),
+    ("lime_stained_glass_pane", 160, 5),
+    ("pink_stained_glass_pane", 160, 6),
+    ("gray_stained_glass_pane", 160, 7),
+    ("light_gray_stained_glass_pane", 160, 8),
+    ("cyan_stained_glass_pane", 160, 9),
+    ("purple_stained_glass_pane", 160, 10),
+    ("blue_stained_glass_pane", 160, 11),
+    ("brown_stained_glass_pane", 160, 12),
+    ("green_stained_glass_pane", 160, 13),
+    ("red_stained_glass_pane", 160, 14),
+    ("black_stained_glass_pane", 160, 15),
+    // minecraft:leaves2
+    ("acacia_leaves", 161, 0),
+    ("dark_oak_leaves", 161, 1),
+    // minecraft:log2
+    ("acacia_log", 162, 0),
+    ("dark_oak_log", 162, 1),
+    // minecraft:carpet
+    ("white_carpet", 171, 0),
+    ("orange_carpet", 171, 1),
+    ("magenta_carpet", 171, 2),
+    ("light_blue_carpet", 171, 3),
+    ("yellow_carpet", 171, 4),
+    ("lime_carpet", 171, 5),
+    ("pink_carpet", 171, 6),
+    ("gray_carpet", 171, 7),
+    ("light_gray_carpet", 171, 8),
+    ("cyan_carpet", 171, 9),
+    ("purple_carpet", 171, 10),
+    ("blue_carpet", 171, 11),
+    ("brown_carpet", 171, 12),
+    ("green_carpet", 171, 13),
+    ("red_carpet", 171, 14),
+    ("black_carpet", 171, 15),
+    // minecraft:double_plant
+    ("sunflower", 175, 0),
+    ("lilac", 175, 1),
+    ("tall_grass", 175, 2),
+    ("large_fern", 175, 3),
+    ("rose_bush", 175, 4),
+    ("peony", 175, 5),
+    // minecraft:coal
+    ("coal", 263, 0),
+    ("charcoal", 263, 1),
+    // minecraft:golden_apple
+    ("golden_apple", 322, 0),
+    ("enchanted_golden_apple", 322, 1),
+    // minecraft:fish
+    ("cod", 349, 0),
+    ("salmon", 349, 1),
+    ("tropical_fish", 349, 2),
+    ("pufferfish", 349, 3),
+    // minecraft:cooked_fish
+    ("cooked_cod", 350, 0),
+    ("cooked_salmon", 350, 1),
+    // minecraft:dye
+    ("ink_sac", 351, 0),
+    ("red_dye", 351, 1),
+    ("green_dye", 351, 2),
+    ("cocoa_beans", 351, 3),
+    ("lapis_lazuli", 351, 4),
+    ("purple_dye", 351, 5),
+    ("cyan_dye", 351, 6),
+    ("light_gray_dye", 351, 7),
+    ("gray_dye", 351, 8),
+    ("pink_dye", 351, 9),
+    ("lime_dye", 351, 10),
+    ("yellow_dye", 351, 11),
+    ("light_blue_dye", 351, 12),
+    ("magenta_dye", 351, 13),
+    ("orange_dye", 351, 14),
+    ("bone_meal", 351, 15),
+    // minecraft:skull
+    ("skeleton_skull", 397, 0),
+    ("wither_skeleton_skull", 397, 1),
+    ("zombie_head", 397, 2),
+    ("player_head", 397, 3),
+    ("creeper_head", 397, 4),
+    ("dragon_head", 397, 5),
+];
+
+/// Item ids win over block ids with the same name, blocks are in [`by_block_name`].
+fn by_name() -> &'static HashMap<&'static str, i16> {
+    static BY_NAME: OnceLock<HashMap<&'static str, i16>> = OnceLock::new();
+    // the table is sorted by id, so items come after the blocks they share a name with
+    BY_NAME.get_or_init(|| IDS.iter().map(|(name, id, _)| (*name, *id)).collect())
+}
+
+fn by_block_name() -> &'static HashMap<&'static str, i16> {
+    static BY_BLOCK_NAME: OnceLock<HashMap<&'static str, i16>> = OnceLock::new();
+    BY_BLOCK_NAME.get_or_init(|| {
+        IDS.iter()
+            .filter(|(_, id, _)| *id < 256)
+            .map(|(name, id, _)| (*name, *id))
+            .collect()
+    })
+}
+
+fn by_id() -> &'static HashMap<i16, (&'static str, MinecraftVersion)> {
+    static BY_ID: OnceLock<HashMap<i16, (&'static str, MinecraftVersion)>> = OnceLock::new();
+    BY_ID.get_or_init(|| {
+        IDS.iter()
+            .map(|(name, id, since)| (*id, (*name, *since)))
+            .collect()
+    })
+}
+
+/// The numeric id of an item, or of a block that has no item of the same name.
 pub fn new_to_old(new_id: &str) -> Option<i16> {
-    // from 1.9.4 dfu, may contain some incorrect ids?
     match new_id {
         "" => Some(0),
-        "minecraft:stone" => Some(1),
-        "minecraft:grass" => Some(2),
-        "minecraft:dirt" => Some(3),
-        "minecraft:cobblestone" => Some(4),
-        "minecraft:planks" => Some(5),
-        "minecraft:sapling" => Some(6),
-        "minecraft:bedrock" => Some(7),
-        "minecraft:flowing_water" => Some(8),
-        "minecraft:water" => Some(9),
-        "minecraft:flowing_lava" => Some(10),
-        "minecraft:lava" => Some(11),
-        "minecraft:sand" => Some(12),
-        "minecraft:gravel" => Some(13),
-        "minecraft:gold_ore" => Some(14),
-        "minecraft:iron_ore" => Some(15),
-        "minecraft:coal_ore" => Some(16),
-        "minecraft:log" => Some(17),
-        "minecraft:leaves" => Some(18),
-        "minecraft:sponge" => Some(19),
-        "minecraft:glass" => Some(20),
-        "minecraft:lapis_ore" => Some(21),
-        "minecraft:lapis_block" => Some(22),
-        "minecraft:dispenser" => Some(23),
-        "minecraft:sandstone" => Some(24),
-        "minecraft:noteblock" => Some(25),
-        "minecraft:golden_rail" => Some(27),
-        "minecraft:detector_rail" => Some(28),
-        "minecraft:sticky_piston" => Some(29),
-        "minecraft:web" => Some(30),
-        "minecraft:tallgrass" => Some(31),
-        "minecraft:deadbush" => Some(32),
-        "minecraft:piston" => Some(33),
-        "minecraft:wool" => Some(35),
-        "minecraft:yellow_flower" => Some(37),
-        "minecraft:red_flower" => Some(38),
-        "minecraft:brown_mushroom" => Some(39),
-        "minecraft:red_mushroom" => Some(40),
-        "minecraft:gold_block" => Some(41),
-        "minecraft:iron_block" => Some(42),
-        "minecraft:double_stone_slab" => Some(43),
-        "minecraft:stone_slab" => Some(44),
-        "minecraft:brick_block" => Some(45),
-        "minecraft:tnt" => Some(46),
-        "minecraft:bookshelf" => Some(47),
-        "minecraft:mossy_cobblestone" => Some(48),
-        "minecraft:obsidian" => Some(49),
-        "minecraft:torch" => Some(50),
-        "minecraft:fire" => Some(51),
-        "minecraft:mob_spawner" => Some(52),
-        "minecraft:oak_stairs" => Some(53),
-        "minecraft:chest" => Some(54),
-        "minecraft:diamond_ore" => Some(56),
-        "minecraft:diamond_block" => Some(57),
-        "minecraft:crafting_table" => Some(58),
-        "minecraft:farmland" => Some(60),
-        "minecraft:furnace" => Some(61),
-        "minecraft:lit_furnace" => Some(62),
-        "minecraft:ladder" => Some(65),
-        "minecraft:rail" => Some(66),
-        "minecraft:stone_stairs" => Some(67),
-        "minecraft:lever" => Some(69),
-        "minecraft:stone_pressure_plate" => Some(70),
-        "minecraft:wooden_pressure_plate" => Some(72),
-        "minecraft:redstone_ore" => Some(73),
-        "minecraft:redstone_torch" => Some(76),
-        "minecraft:stone_button" => Some(77),
-        "minecraft:snow_layer" => Some(78),
-        "minecraft:ice" => Some(79),
-        "minecraft:snow" => Some(80),
-        "minecraft:cactus" => Some(81),
-        "minecraft:clay" => Some(82),
-        "minecraft:jukebox" => Some(84),
-        "minecraft:fence" => Some(85),
-        "minecraft:pumpkin" => Some(86),
-        "minecraft:netherrack" => Some(87),
-        "minecraft:soul_sand" => Some(88),
-        "minecraft:glowstone" => Some(89),
-        "minecraft:portal" => Some(90),
-        "minecraft:lit_pumpkin" => Some(91),
-        "minecraft:stained_glass" => Some(95),
-        "minecraft:trapdoor" => Some(96),
-        "minecraft:monster_egg" => Some(97),
-        "minecraft:stonebrick" => Some(98),
-        "minecraft:brown_mushroom_block" => Some(99),
-        "minecraft:red_mushroom_block" => Some(100),
-        "minecraft:iron_bars" => Some(101),
-        "minecraft:glass_pane" => Some(102),
-        "minecraft:melon_block" => Some(103),
-        "minecraft:vine" => Some(106),
-        "minecraft:fence_gate" => Some(107),
-        "minecraft:brick_stairs" => Some(108),
-        "minecraft:stone_brick_stairs" => Some(109),
-        "minecraft:mycelium" => Some(110),
-        "minecraft:waterlily" => Some(111),
-        "minecraft:nether_brick" => Some(112),
-        "minecraft:nether_brick_fence" => Some(113),
-        "minecraft:nether_brick_stairs" => Some(114),
-        "minecraft:enchanting_table" => Some(116),
-        "minecraft:end_portal" => Some(119),
-        "minecraft:end_portal_frame" => Some(120),
-        "minecraft:end_stone" => Some(121),
-        "minecraft:dragon_egg" => Some(122),
-        "minecraft:redstone_lamp" => Some(123),
-        "minecraft:double_wooden_slab" => Some(125),
-        "minecraft:wooden_slab" => Some(126),
-        "minecraft:cocoa" => Some(127),
-        "minecraft:sandstone_stairs" => Some(128),
-        "minecraft:emerald_ore" => Some(129),
-        "minecraft:ender_chest" => Some(130),
-        "minecraft:tripwire_hook" => Some(131),
-        "minecraft:emerald_block" => Some(133),
-        "minecraft:spruce_stairs" => Some(134),
-        "minecraft:birch_stairs" => Some(135),
-        "minecraft:jungle_stairs" => Some(136),
-        "minecraft:command_block" => Some(137),
-        "minecraft:beacon" => Some(138),
-        "minecraft:cobblestone_wall" => Some(139),
-        "minecraft:carrots" => Some(141),
-        "minecraft:potatoes" => Some(142),
-        "minecraft:wooden_button" => Some(143),
-        "minecraft:anvil" => Some(145),
-        "minecraft:trapped_chest" => Some(146),
-        "minecraft:light_weighted_pressure_plate" => Some(147),
-        "minecraft:heavy_weighted_pressure_plate" => Some(148),
-        "minecraft:daylight_detector" => Some(151),
-        "minecraft:redstone_block" => Some(152),
-        "minecraft:quartz_ore" => Some(153),
-        "minecraft:hopper" => Some(154),
-        "minecraft:quartz_block" => Some(155),
-        "minecraft:quartz_stairs" => Some(156),
-        "minecraft:activator_rail" => Some(157),
-        "minecraft:dropper" => Some(158),
-        "minecraft:stained_hardened_clay" => Some(159),
-        "minecraft:stained_glass_pane" => Some(160),
-        "minecraft:leaves2" => Some(161),
-        "minecraft:log2" => Some(162),
-        "minecraft:acacia_stairs" => Some(163),
-        "minecraft:dark_oak_stairs" => Some(164),
-        "minecraft:hay_block" => Some(170),
-        "minecraft:carpet" => Some(171),
-        "minecraft:hardened_clay" => Some(172),
-        "minecraft:coal_block" => Some(173),
-        "minecraft:packed_ice" => Some(174),
-        "minecraft:double_plant" => Some(175),
-        "minecraft:iron_shovel" => Some(256),
-        "minecraft:iron_pickaxe" => Some(257),
-        "minecraft:iron_axe" => Some(258),
-        "minecraft:flint_and_steel" => Some(259),
-        "minecraft:apple" => Some(260),
-        "minecraft:bow" => Some(261),
-        "minecraft:arrow" => Some(262),
-        "minecraft:coal" => Some(263),
-        "minecraft:diamond" => Some(264),
-        "minecraft:iron_ingot" => Some(265),
-        "minecraft:gold_ingot" => Some(266),
-        "minecraft:iron_sword" => Some(267),
-        "minecraft:wooden_sword" => Some(268),
-        "minecraft:wooden_shovel" => Some(269),
-        "minecraft:wooden_pickaxe" => Some(270),
-        "minecraft:wooden_axe" => Some(271),
-        "minecraft:stone_sword" => Some(272),
-        "minecraft:stone_shovel" => Some(273),
-        "minecraft:stone_pickaxe" => Some(274),
-        "minecraft:stone_axe" => Some(275),
-        "minecraft:diamond_sword" => Some(276),
-        "minecraft:diamond_shovel" => Some(277),
-        "minecraft:diamond_pickaxe" => Some(278),
-        "minecraft:diamond_axe" => Some(279),
-        "minecraft:stick" => Some(280),
-        "minecraft:bowl" => Some(281),
-        "minecraft:mushroom_stew" => Some(282),
-        "minecraft:golden_sword" => Some(283),
-        "minecraft:golden_shovel" => Some(284),
-        "minecraft:golden_pickaxe" => Some(285),
-        "minecraft:golden_axe" => Some(286),
-        "minecraft:string" => Some(287),
-        "minecraft:feather" => Some(288),
-        "minecraft:gunpowder" => Some(289),
-        "minecraft:wooden_hoe" => Some(290),
-        "minecraft:stone_hoe" => Some(291),
-        "minecraft:iron_hoe" => Some(292),
-        "minecraft:diamond_hoe" => Some(293),
-        "minecraft:golden_hoe" => Some(294),
-        "minecraft:wheat_seeds" => Some(295),
-        "minecraft:wheat" => Some(296),
-        "minecraft:bread" => Some(297),
-        "minecraft:leather_helmet" => Some(298),
-        "minecraft:leather_chestplate" => Some(299),
-        "minecraft:leather_leggings" => Some(300),
-        "minecraft:leather_boots" => Some(301),
-        "minecraft:chainmail_helmet" => Some(302),
-        "minecraft:chainmail_chestplate" => Some(303),
-        "minecraft:chainmail_leggings" => Some(304),
-        "minecraft:chainmail_boots" => Some(305),
-        "minecraft:iron_helmet" => Some(306),
-        "minecraft:iron_chestplate" => Some(307),
-        "minecraft:iron_leggings" => Some(308),
-        "minecraft:iron_boots" => Some(309),
-        "minecraft:diamond_helmet" => Some(310),
-        "minecraft:diamond_chestplate" => Some(311),
-        "minecraft:diamond_leggings" => Some(312),
-        "minecraft:diamond_boots" => Some(313),
-        "minecraft:golden_helmet" => Some(314),
-        "minecraft:golden_chestplate" => Some(315),
-        "minecraft:golden_leggings" => Some(316),
-        "minecraft:golden_boots" => Some(317),
-        "minecraft:flint" => Some(318),
-        "minecraft:porkchop" => Some(319),
-        "minecraft:cooked_porkchop" => Some(320),
-        "minecraft:painting" => Some(321),
-        "minecraft:golden_apple" => Some(322),
-        "minecraft:sign" => Some(323),
-        "minecraft:wooden_door" => Some(324),
-        "minecraft:bucket" => Some(325),
-        "minecraft:water_bucket" => Some(326),
-        "minecraft:lava_bucket" => Some(327),
-        "minecraft:minecart" => Some(328),
-        "minecraft:saddle" => Some(329),
-        "minecraft:iron_door" => Some(330),
-        "minecraft:redstone" => Some(331),
-        "minecraft:snowball" => Some(332),
-        "minecraft:boat" => Some(333),
-        "minecraft:leather" => Some(334),
-        "minecraft:milk_bucket" => Some(335),
-        "minecraft:brick" => Some(336),
-        "minecraft:clay_ball" => Some(337),
-        "minecraft:reeds" => Some(338),
-        "minecraft:paper" => Some(339),
-        "minecraft:book" => Some(340),
-        "minecraft:slime_ball" => Some(341),
-        "minecraft:chest_minecart" => Some(342),
-        "minecraft:furnace_minecart" => Some(343),
-        "minecraft:egg" => Some(344),
-        "minecraft:compass" => Some(345),
-        "minecraft:fishing_rod" => Some(346),
-        "minecraft:clock" => Some(347),
-        "minecraft:glowstone_dust" => Some(348),
-        "minecraft:fish" => Some(349),
-        "minecraft:cooked_fished" => Some(350),
-        "minecraft:dye" => Some(351),
-        "minecraft:bone" => Some(352),
-        "minecraft:sugar" => Some(353),
-        "minecraft:cake" => Some(354),
-        "minecraft:bed" => Some(355),
-        "minecraft:repeater" => Some(356),
-        "minecraft:cookie" => Some(357),
-        "minecraft:filled_map" => Some(358),
-        "minecraft:shears" => Some(359),
-        "minecraft:melon" => Some(360),
-        "minecraft:pumpkin_seeds" => Some(361),
-        "minecraft:melon_seeds" => Some(362),
-        "minecraft:beef" => Some(363),
-        "minecraft:cooked_beef" => Some(364),
-        "minecraft:chicken" => Some(365),
-        "minecraft:cooked_chicken" => Some(366),
-        "minecraft:rotten_flesh" => Some(367),
-        "minecraft:ender_pearl" => Some(368),
-        "minecraft:blaze_rod" => Some(369),
-        "minecraft:ghast_tear" => Some(370),
-        "minecraft:gold_nugget" => Some(371),
-        "minecraft:nether_wart" => Some(372),
-        "minecraft:potion" => Some(373),
-        "minecraft:glass_bottle" => Some(374),
-        "minecraft:spider_eye" => Some(375),
-        "minecraft:fermented_spider_eye" => Some(376),
-        "minecraft:blaze_powder" => Some(377),
-        "minecraft:magma_cream" => Some(378),
-        "minecraft:brewing_stand" => Some(379),
-        "minecraft:cauldron" => Some(380),
-        "minecraft:ender_eye" => Some(381),
-        "minecraft:speckled_melon" => Some(382),
-        "minecraft:spawn_egg" => Some(383),
-        "minecraft:experience_bottle" => Some(384),
-        "minecraft:fire_charge" => Some(385),
-        "minecraft:writable_book" => Some(386),
-        "minecraft:written_book" => Some(387),
-        "minecraft:emerald" => Some(388),
-        "minecraft:item_frame" => Some(389),
-        "minecraft:flower_pot" => Some(390),
-        "minecraft:carrot" => Some(391),
-        "minecraft:potato" => Some(392),
-        "minecraft:baked_potato" => Some(393),
-        "minecraft:poisonous_potato" => Some(394),
-        "minecraft:map" => Some(395),
-        "minecraft:golden_carrot" => Some(396),
-        "minecraft:skull" => Some(397),
-        "minecraft:carrot_on_a_stick" => Some(398),
-        "minecraft:nether_star" => Some(399),
-        "minecraft:pumpkin_pie" => Some(400),
-        "minecraft:fireworks" => Some(401),
-        "minecraft:firework_charge" => Some(402),
-        "minecraft:enchanted_book" => Some(403),
-        "minecraft:comparator" => Some(404),
-        "minecraft:netherbrick" => Some(405),
-        "minecraft:quartz" => Some(406),
-        "minecraft:tnt_minecart" => Some(407),
-        "minecraft:hopper_minecart" => Some(408),
-        "minecraft:iron_horse_armor" => Some(417),
-        "minecraft:golden_horse_armor" => Some(418),
-        "minecraft:diamond_horse_armor" => Some(419),
-        "minecraft:lead" => Some(420),
-        "minecraft:name_tag" => Some(421),
-        "minecraft:command_block_minecart" => Some(422),
-        "minecraft:record_13" => Some(2256),
-        "minecraft:record_cat" => Some(2257),
-        "minecraft:record_blocks" => Some(2258),
-        "minecraft:record_chirp" => Some(2259),
-        "minecraft:record_far" => Some(2260),
-        "minecraft:record_mall" => Some(2261),
-        "minecraft:record_mellohi" => Some(2262),
-        "minecraft:record_stal" => Some(2263),
-        "minecraft:record_strad" => Some(2264),
-        "minecraft:record_ward" => Some(2265),
-        "minecraft:record_11" => Some(2266),
-        "minecraft:record_wait" => Some(2267),
+        _ => by_name().get(new_id).copied(),
+    }
+}
+
+/// The numeric id of a block, for places that only hold blocks like tile ticks.
+pub fn block_new_to_old(new_id: &str) -> Option<i16> {
+    match new_id {
+        "" => Some(0),
+        _ => by_block_name().get(new_id).copied(),
+    }
+}
+
+pub fn old_to_new(old_id: i16) -> Option<&'static str> {
+    by_id().get(&old_id).map(|(name, _)| *name)
+}
+
+/// The version that introduced `old_id`.
+pub fn since(old_id: i16) -> Option<MinecraftVersion> {
+    by_id().get(&old_id).map(|(_, since)| *since)
+}
+
+/// Whether `target` has `old_id`. Ids missing from the table, like modded ones, are
+/// assumed to be there.
+pub fn exists_in(old_id: i16, target: MinecraftVersion) -> bool {
+    since(old_id).is_none_or(|since| since <= target)
+}
+
+fn variants_by_name() -> &'static HashMap<&'static str, (i16, i16)> {
+    static BY_NAME: OnceLock<HashMap<&'static str, (i16, i16)>> = OnceLock::new();
+    BY_NAME.get_or_init(|| {
+        VARIANTS
+            .iter()
+            .map(|(name, id, damage)| (*name, (*id, *damage)))
+            .collect()
+    })
+}
+
+fn variants_by_id() -> &'static HashMap<(i16, i16), &'static str> {
+    static BY_ID: OnceLock<HashMap<(i16, i16), &'static str>> = OnceLock::new();
+    BY_ID.get_or_init(|| {
+        VARIANTS
+            .iter()
+            .map(|(name, id, damage)| ((*id, *damage), *name))
+            .collect()
+    })
+}
+
+/// The numeric id and damage of a variant name like `red_wool` or `lapis_lazuli`.
+pub fn name_to_variant(name: &str) -> Option<(i16, i16)> {
+    variants_by_name().get(name).copied()
+}
+
+/// The variant name of `id:damage`, e.g. `red_wool` for `35:14`.
+pub fn variant_to_name(id: i16, damage: i16) -> Option<&'static str> {
+    variants_by_id().get(&(id, damage)).copied()
+}
+
+/// The most readable name of `id:damage` there is: the variant name, else the namespaced
+/// id (with the damage if it isn't 0), else the numbers.
+pub fn describe(id: i16, damage: i16) -> String {
+    if let Some(name) = variant_to_name(id, damage) {
+        return name.to_string();
+    }
+
+    match (old_to_new(id), damage) {
+        (Some(name), 0) => name.to_string(),
+        (Some(name), _) => format!("{name}:{damage}"),
+        (None, _) => format!("{id}:{damage}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        assert_eq!(by_id().len(), IDS.len(), "duplicate id in IDS");
+
+        for (name, id, _) in IDS {
+            assert_eq!(old_to_new(*id), Some(*name));
+
+            if *id < 256 {
+                assert_eq!(block_new_to_old(name), Some(*id));
+            }
+            // a block only loses its name to an item of the same name
+            let named = new_to_old(name).unwrap();
+            assert!(named == *id || (*id < 256 && old_to_new(named) == Some(*name)));
+        }
+
+        assert_eq!(new_to_old("minecraft:bed"), Some(355));
+        assert_eq!(block_new_to_old("minecraft:bed"), Some(26));
+        assert_eq!(block_new_to_old("minecraft:iron_sword"), None);
+    }
+
+    #[test]
+    fn ids_since() {
+        use MinecraftVersion::*;
+
+        assert_eq!(since(1), Some(V1_4_5));
+        assert_eq!(since(165), Some(V1_8));
+        assert_eq!(since(442), Some(V1_9_4));
+        assert_eq!(new_to_old("minecraft:end_crystal"), Some(426));
+        assert_eq!(since(426), Some(V1_9_4));
+        assert!(exists_in(165, V1_8));
+        assert!(!exists_in(165, V1_7_10));
+        assert!(exists_in(4000, V1_4_7));
+    }
+
+    #[test]
+    fn variants_round_trip() {
+        assert_eq!(
+            variants_by_name().len(),
+            VARIANTS.len(),
+            "duplicate name in VARIANTS"
+        );
+        assert_eq!(
+            variants_by_id().len(),
+            VARIANTS.len(),
+            "duplicate id in VARIANTS"
+        );
+
+        for (name, id, damage) in VARIANTS {
+            assert_eq!(name_to_variant(name), Some((*id, *damage)));
+            assert_eq!(variant_to_name(*id, *damage), Some(*name));
+            assert!(old_to_new(*id).is_some(), "{name} has an unknown id");
+        }
 
-        _ => None,
+        assert_eq!(name_to_variant("red_wool"), Some((35, 14)));
+        assert_eq!(variant_to_name(351, 4), Some("lapis_lazuli"));
+        assert_eq!(describe(35, 14), "red_wool");
+        assert_eq!(describe(276, 12), "minecraft:diamond_sword:12");
+        assert_eq!(describe(4000, 1), "4000:1");
     }
 }
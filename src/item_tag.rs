@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+use fastnbt::Value;
+
+use crate::chat;
+use crate::enchantments;
+use crate::fixer::FixContext;
+use crate::version::MinecraftVersion;
+use crate::NbtValueExt;
+
+const WRITTEN_BOOK: i16 = 387;
+
+/// A tag key older versions read differently or not at all.
+struct TagRule {
+    key: &'static str,
+    /// The first version reading the key as it's stored now.
+    since: MinecraftVersion,
+    /// Turns the value into what the version before `since` reads, `None` removes it.
+    rewrite: fn(Value) -> Option<Value>,
+}
+
+const fn remove(key: &'static str, since: MinecraftVersion) -> TagRule {
+    TagRule {
+        key,
+        since,
+        rewrite: |_| None,
+    }
+}
+
+const fn rewrite(
+    key: &'static str,
+    since: MinecraftVersion,
+    rewrite: fn(Value) -> Option<Value>,
+) -> TagRule {
+    TagRule {
+        key,
+        since,
+        rewrite,
+    }
+}
+
+/// Rules for the keys of every item's tag, newest first.
+const TAG_RULES: &[TagRule] = {
+    use MinecraftVersion::*;
+
+    &[
+        remove("generation", V1_9_4),
+        rewrite("AttributeModifiers", V1_9_4, attribute_modifiers_1_9),
+        remove("resolved", V1_8),
+        remove("CanDestroy", V1_8),
+        remove("CanPlaceOn", V1_8),
+        remove("HideFlags", V1_8),
+        remove("BlockEntityTag", V1_8),
+        rewrite("SkullOwner", V1_7_6, skull_owner_1_7_6),
+        remove("Unbreakable", V1_7),
+        remove("AttributeModifiers", V1_6),
+    ]
+};
+
+/// Rewrites the `tag` of an item stack for the target, after its id was downgraded. An
+/// emptied tag should be removed.
+pub fn downgrade_item_tag(
+    id: Option<i16>,
+    tag: &mut HashMap<String, Value>,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    for rule in TAG_RULES {
+        if ctx.target >= rule.since {
+            continue;
+        }
+
+        if let Some(value) = tag.remove(rule.key) {
+            if let Some(value) = (rule.rewrite)(value) {
+                tag.insert(rule.key.into(), value);
+            }
+        }
+    }
+
+    for key in ["ench", "StoredEnchantments"] {
+        if let Some(enchantments) = tag.get_mut(key).and_then(NbtValueExt::as_list) {
+            downgrade_enchantments(enchantments, ctx);
+
+            if enchantments.is_empty() {
+                tag.remove(key);
+            }
+        }
+    }
+
+    // 1.8 books have JSON pages, `title` and `author` are plain text in every version
+    if id == Some(WRITTEN_BOOK) && ctx.target < MinecraftVersion::V1_8 {
+        if let Some(pages) = tag.get_mut("pages").and_then(NbtValueExt::as_list) {
+            for page in pages.iter_mut() {
+                if let Value::String(text) = page {
+                    *text = flatten(text);
+                }
+            }
+        }
+    }
+
+    if let Some(display) = tag.get_mut("display").and_then(NbtValueExt::as_compound) {
+        downgrade_display(display);
+
+        if display.is_empty() {
+            tag.remove("display");
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes or substitutes enchantments the target doesn't have and clamps levels, keeping
+/// the highest level when a substitute is already there.
+fn downgrade_enchantments(enchantments: &mut Vec<Value>, ctx: &FixContext) {
+    let mut downgraded: Vec<Value> = vec![];
+
+    for mut enchantment in enchantments.drain(..) {
+        let Some(compound) = enchantment.as_compound() else {
+            continue;
+        };
+        let (Some(Value::Short(id)), Some(Value::Short(level))) =
+            (compound.get("id"), compound.get("lvl"))
+        else {
+            downgraded.push(enchantment);
+            continue;
+        };
+
+        let Some((id, level)) = enchantments::downgrade(*id, *level, ctx.target) else {
+            continue;
+        };
+        compound.insert("id".into(), Value::Short(id));
+        compound.insert("lvl".into(), Value::Short(level));
+
+        let existing = downgraded.iter_mut().find_map(|other| match other {
+            Value::Compound(other) if other.get("id") == Some(&Value::Short(id)) => Some(other),
+            _ => None,
+        });
+        match existing {
+            Some(existing) => {
+                if !matches!(existing.get("lvl"), Some(Value::Short(other)) if *other >= level) {
+                    existing.insert("lvl".into(), Value::Short(level));
+                }
+            }
+            None => downgraded.push(enchantment),
+        }
+    }
+
+    *enchantments = downgraded;
+}
+
+/// `Name` and `Lore` are § formatted text in every version, but some 1.8 tools wrote chat
+/// components there.
+fn downgrade_display(display: &mut HashMap<String, Value>) {
+    if let Some(Value::String(name)) = display.get_mut("Name") {
+        *name = flatten_component(name);
+    }
+
+    if let Some(lore) = display.get_mut("Lore").and_then(NbtValueExt::as_list) {
+        for line in lore.iter_mut() {
+            if let Value::String(text) = line {
+                *text = flatten_component(text);
+            }
+        }
+    }
+}
+
+/// JSON chat text as legacy text, or the text as it was if it isn't JSON.
+fn flatten(text: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(text)
+        .map(|component| chat::to_legacy(&component))
+        .unwrap_or_else(|_| text.to_owned())
+}
+
+/// Like [`flatten`], but only for objects and arrays, so a name like `"5"` stays quoted.
+fn flatten_component(text: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(component @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => {
+            chat::to_legacy(&component)
+        }
+        _ => text.to_owned(),
+    }
+}
+
+/// Modifiers only apply to the slot they're in before 1.9.
+fn attribute_modifiers_1_9(mut modifiers: Value) -> Option<Value> {
+    for modifier in modifiers.as_list()? {
+        if let Some(modifier) = modifier.as_compound() {
+            modifier.remove("Slot");
+        }
+    }
+
+    Some(modifiers)
+}
+
+/// Skulls were owned by a player name before game profiles, a profile without one is dropped.
+fn skull_owner_1_7_6(owner: Value) -> Option<Value> {
+    match owner {
+        Value::Compound(mut owner) => match owner.remove("Name") {
+            Some(Value::String(name)) => Some(Value::String(name)),
+            _ => None,
+        },
+        owner => Some(owner),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::MinecraftVersion::*;
+
+    fn string(text: &str) -> Value {
+        Value::String(text.into())
+    }
+
+    fn downgrade(
+        id: i16,
+        tag: &[(&str, Value)],
+        target: MinecraftVersion,
+    ) -> HashMap<String, Value> {
+        let mut tag = tag
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        downgrade_item_tag(Some(id), &mut tag, &mut FixContext::new(target)).unwrap();
+        tag
+    }
+
+    #[test]
+    fn written_book_pages_are_flattened() {
+        let pages = Value::List(vec![
+            string(r#"{"text":"Chapter 1","bold":true}"#),
+            string(r#""plain""#),
+            string("not json"),
+        ]);
+        let tag = [("pages", pages.clone()), ("title", string("Story"))];
+
+        let book = downgrade(WRITTEN_BOOK, &tag, V1_7_10);
+        assert_eq!(
+            book["pages"],
+            Value::List(vec![
+                string("§lChapter 1"),
+                string("plain"),
+                string("not json")
+            ])
+        );
+        assert_eq!(book["title"], string("Story"));
+
+        // 1.8 reads them as they are, and writable books were always plain text
+        assert_eq!(downgrade(WRITTEN_BOOK, &tag, V1_8)["pages"], pages);
+        assert_eq!(downgrade(386, &tag, V1_7_10)["pages"], pages);
+    }
+
+    #[test]
+    fn display_components_are_flattened() {
+        let display = Value::Compound(HashMap::from([
+            (
+                "Name".to_string(),
+                string(r#"{"text":"Blade","color":"aqua"}"#),
+            ),
+            (
+                "Lore".to_string(),
+                Value::List(vec![
+                    string(r#"["a",{"text":"b","italic":true}]"#),
+                    string("5"),
+                ]),
+            ),
+        ]));
+
+        let tag = downgrade(276, &[("display", display)], V1_7_10);
+        let Value::Compound(display) = &tag["display"] else {
+            panic!("display isn't a compound");
+        };
+        assert_eq!(display["Name"], string("§bBlade"));
+        assert_eq!(
+            display["Lore"],
+            Value::List(vec![string("a§ob"), string("5")])
+        );
+    }
+
+    #[test]
+    fn tag_rules_apply_newest_first() {
+        let modifier = |slot: Option<&str>| {
+            let mut modifier = HashMap::from([
+                ("AttributeName".to_string(), string("generic.attackDamage")),
+                ("Amount".to_string(), Value::Double(4.0)),
+            ]);
+            if let Some(slot) = slot {
+                modifier.insert("Slot".to_string(), string(slot));
+            }
+            Value::List(vec![Value::Compound(modifier)])
+        };
+        let tag = [
+            ("AttributeModifiers", modifier(Some("mainhand"))),
+            ("HideFlags", Value::Int(1)),
+            ("Unbreakable", Value::Byte(1)),
+        ];
+
+        assert_eq!(downgrade(276, &tag, V1_9_4).len(), 3);
+
+        // modifiers lose their slot, and then go away entirely
+        let sword = downgrade(276, &tag, V1_7_10);
+        assert_eq!(sword["AttributeModifiers"], modifier(None));
+        assert!(!sword.contains_key("HideFlags"));
+        assert_eq!(sword["Unbreakable"], Value::Byte(1));
+
+        let sword = downgrade(276, &tag, V1_6_4);
+        assert!(sword.contains_key("AttributeModifiers"));
+        assert!(!sword.contains_key("Unbreakable"));
+
+        assert!(downgrade(276, &tag, V1_5_2).is_empty());
+    }
+
+    #[test]
+    fn skull_owners_become_names() {
+        let profile = Value::Compound(HashMap::from([
+            (
+                "Id".to_string(),
+                string("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+            ),
+            ("Name".to_string(), string("Notch")),
+        ]));
+        assert_eq!(skull_owner_1_7_6(profile.clone()), Some(string("Notch")));
+        assert_eq!(skull_owner_1_7_6(string("Notch")), Some(string("Notch")));
+
+        let nameless = Value::Compound(HashMap::from([(
+            "Id".to_string(),
+            string("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+        )]));
+        assert_eq!(skull_owner_1_7_6(nameless), None);
+
+        let tag = [("SkullOwner", profile.clone())];
+        assert_eq!(downgrade(397, &tag, V1_7_6)["SkullOwner"], profile);
+        assert_eq!(downgrade(397, &tag, V1_7_2)["SkullOwner"], string("Notch"));
+    }
+
+    fn enchantments(enchantments: &[(i16, i16)]) -> Value {
+        Value::List(
+            enchantments
+                .iter()
+                .map(|(id, level)| {
+                    Value::Compound(HashMap::from([
+                        ("id".to_string(), Value::Short(*id)),
+                        ("lvl".to_string(), Value::Short(*level)),
+                    ]))
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn enchantments_are_substituted_and_clamped() {
+        // mending becomes unbreaking, merged with the one already there at the higher level
+        let tag = [("ench", enchantments(&[(70, 1), (34, 2), (16, 10)]))];
+        assert_eq!(
+            downgrade(276, &tag, V1_8)["ench"],
+            enchantments(&[(34, 2), (16, 5)])
+        );
+        let tag = [("ench", enchantments(&[(34, 3), (70, 1)]))];
+        assert_eq!(downgrade(276, &tag, V1_8)["ench"], enchantments(&[(34, 3)]));
+
+        // depth strider is gone before 1.8, a modded id stays as it is
+        let tag = [("StoredEnchantments", enchantments(&[(8, 3), (100, 7)]))];
+        assert_eq!(
+            downgrade(403, &tag, V1_7_10)["StoredEnchantments"],
+            enchantments(&[(100, 7)])
+        );
+        assert_eq!(
+            downgrade(403, &tag, V1_8)["StoredEnchantments"],
+            enchantments(&[(8, 3), (100, 7)])
+        );
+    }
+
+    #[test]
+    fn emptied_enchantments_are_removed() {
+        let tag = [
+            ("ench", enchantments(&[(9, 2)])),
+            ("StoredEnchantments", enchantments(&[(61, 1), (62, 3)])),
+            ("RepairCost", Value::Int(1)),
+        ];
+
+        let boots = downgrade(313, &tag, V1_6_4);
+        assert!(!boots.contains_key("ench"));
+        assert!(!boots.contains_key("StoredEnchantments"));
+        assert_eq!(boots["RepairCost"], Value::Int(1));
+
+        assert!(downgrade(313, &tag, V1_7).contains_key("StoredEnchantments"));
+    }
+}
@@ -0,0 +1,353 @@
+use crate::version::MinecraftVersion;
+
+/// An item (id and damage) that doesn't exist before `since`, and what to give instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemSubstitution {
+    pub since: MinecraftVersion,
+    pub id: i16,
+    /// `None` matches any damage.
+    pub damage: Option<i16>,
+    pub replacement_id: i16,
+    /// `None` keeps the original damage, like the colour of stained clay turned into wool.
+    pub replacement_damage: Option<i16>,
+}
+
+/// Any damage of `id`.
+const fn item(
+    since: MinecraftVersion,
+    id: i16,
+    replacement_id: i16,
+    replacement_damage: i16,
+) -> ItemSubstitution {
+    ItemSubstitution {
+        since,
+        id,
+        damage: None,
+        replacement_id,
+        replacement_damage: Some(replacement_damage),
+    }
+}
+
+/// Only `id:damage`.
+const fn variant(
+    since: MinecraftVersion,
+    id: i16,
+    damage: i16,
+    replacement_id: i16,
+    replacement_damage: i16,
+) -> ItemSubstitution {
+    ItemSubstitution {
+        since,
+        id,
+        damage: Some(damage),
+        replacement_id,
+        replacement_damage: Some(replacement_damage),
+    }
+}
+
+/// Any damage of `id`, carried over to the replacement.
+const fn keep_damage(since: MinecraftVersion, id: i16, replacement_id: i16) -> ItemSubstitution {
+    ItemSubstitution {
+        since,
+        id,
+        damage: None,
+        replacement_id,
+        replacement_damage: None,
+    }
+}
+
+/// Substitutions for items old clients don't know, newest first. Block items follow
+/// [`crate::blocks::DEFAULT_SUBSTITUTIONS`].
+pub const DEFAULT_SUBSTITUTIONS: &[ItemSubstitution] = {
+    use MinecraftVersion::*;
+
+    &[
+        // 1.9
+        item(V1_9_4, 198, 50, 0),      // end rod -> torch
+        item(V1_9_4, 199, 38, 0),      // chorus plant -> poppy
+        item(V1_9_4, 200, 38, 0),      // chorus flower -> poppy
+        item(V1_9_4, 201, 35, 10),     // purpur block -> purple wool
+        item(V1_9_4, 202, 35, 10),     // purpur pillar -> purple wool
+        item(V1_9_4, 203, 156, 0),     // purpur stairs -> quartz stairs
+        item(V1_9_4, 205, 44, 7),      // purpur slab -> quartz slab
+        item(V1_9_4, 206, 121, 0),     // end stone bricks -> end stone
+        item(V1_9_4, 208, 2, 0),       // grass path -> grass
+        item(V1_9_4, 210, 137, 0),     // repeating command block -> command block
+        item(V1_9_4, 211, 137, 0),     // chain command block -> command block
+        item(V1_9_4, 255, 1, 0),       // structure block -> stone
+        item(V1_9_4, 426, 381, 0),     // end crystal -> eye of ender
+        item(V1_9_4, 432, 260, 0),     // chorus fruit -> apple
+        item(V1_9_4, 433, 351, 5),     // popped chorus fruit -> purple dye
+        item(V1_9_4, 434, 391, 0),     // beetroot -> carrot
+        item(V1_9_4, 435, 295, 0),     // beetroot seeds -> wheat seeds
+        item(V1_9_4, 436, 282, 0),     // beetroot soup -> mushroom stew
+        item(V1_9_4, 437, 374, 0),     // dragon's breath -> glass bottle
+        item(V1_9_4, 438, 373, 16384), // splash potion -> splash water bottle
+        item(V1_9_4, 439, 262, 0),     // spectral arrow -> arrow
+        item(V1_9_4, 440, 262, 0),     // tipped arrow -> arrow
+        item(V1_9_4, 441, 373, 16384), // lingering potion -> splash water bottle
+        item(V1_9_4, 442, 265, 0),     // shield -> iron ingot
+        item(V1_9_4, 443, 299, 0),     // elytra -> leather tunic
+        item(V1_9_4, 444, 333, 0),     // spruce boat -> boat
+        item(V1_9_4, 445, 333, 0),     // birch boat -> boat
+        item(V1_9_4, 446, 333, 0),     // jungle boat -> boat
+        item(V1_9_4, 447, 333, 0),     // acacia boat -> boat
+        item(V1_9_4, 448, 333, 0),     // dark oak boat -> boat
+        // 1.9 spawn eggs and heads
+        variant(V1_9_4, 383, 69, 383, 60), // shulker spawn egg -> silverfish
+        variant(V1_9_4, 397, 5, 397, 4),   // dragon head -> creeper head
+        // 1.8
+        variant(V1_8, 1, 1, 1, 0),   // granite
+        variant(V1_8, 1, 2, 1, 0),   // polished granite
+        variant(V1_8, 1, 3, 1, 0),   // diorite
+        variant(V1_8, 1, 4, 1, 0),   // polished diorite
+        variant(V1_8, 1, 5, 1, 0),   // andesite
+        variant(V1_8, 1, 6, 1, 0),   // polished andesite
+        variant(V1_8, 19, 1, 19, 0), // wet sponge
+        item(V1_8, 165, 35, 5),      // slime block -> lime wool
+        item(V1_8, 166, 20, 0),      // barrier -> glass
+        item(V1_8, 167, 96, 0),      // iron trapdoor -> trapdoor
+        item(V1_8, 168, 98, 0),      // prismarine -> stone bricks
+        item(V1_8, 169, 89, 0),      // sea lantern -> glowstone
+        keep_damage(V1_8, 179, 24),  // red sandstone -> sandstone
+        item(V1_8, 180, 128, 0),     // red sandstone stairs -> sandstone stairs
+        item(V1_8, 182, 44, 1),      // red sandstone slab -> sandstone slab
+        item(V1_8, 183, 107, 0),     // spruce fence gate
+        item(V1_8, 184, 107, 0),     // birch fence gate
+        item(V1_8, 185, 107, 0),     // jungle fence gate
+        item(V1_8, 186, 107, 0),     // dark oak fence gate
+        item(V1_8, 187, 107, 0),     // acacia fence gate
+        item(V1_8, 188, 85, 0),      // spruce fence
+        item(V1_8, 189, 85, 0),      // birch fence
+        item(V1_8, 190, 85, 0),      // jungle fence
+        item(V1_8, 191, 85, 0),      // dark oak fence
+        item(V1_8, 192, 85, 0),      // acacia fence
+        item(V1_8, 409, 406, 0),     // prismarine shard -> nether quartz
+        item(V1_8, 410, 348, 0),     // prismarine crystals -> glowstone dust
+        item(V1_8, 411, 365, 0),     // raw rabbit -> raw chicken
+        item(V1_8, 412, 366, 0),     // cooked rabbit -> cooked chicken
+        item(V1_8, 413, 282, 0),     // rabbit stew -> mushroom stew
+        item(V1_8, 414, 288, 0),     // rabbit's foot -> feather
+        item(V1_8, 415, 334, 0),     // rabbit hide -> leather
+        item(V1_8, 416, 280, 0),     // armor stand -> stick
+        item(V1_8, 423, 319, 0),     // raw mutton -> raw porkchop
+        item(V1_8, 424, 320, 0),     // cooked mutton -> cooked porkchop
+        item(V1_8, 425, 323, 0),     // banner -> sign
+        item(V1_8, 427, 324, 0),     // spruce door
+        item(V1_8, 428, 324, 0),     // birch door
+        item(V1_8, 429, 324, 0),     // jungle door
+        item(V1_8, 430, 324, 0),     // acacia door
+        item(V1_8, 431, 324, 0),     // dark oak door
+        // 1.8 spawn eggs
+        variant(V1_8, 383, 101, 383, 93), // rabbit spawn egg -> chicken
+        variant(V1_8, 383, 67, 383, 60),  // endermite spawn egg -> silverfish
+        variant(V1_8, 383, 68, 383, 94),  // guardian spawn egg -> squid
+        // 1.7
+        variant(V1_7, 3, 1, 3, 0),     // coarse dirt
+        variant(V1_7, 3, 2, 3, 0),     // podzol
+        variant(V1_7, 5, 4, 5, 0),     // acacia planks
+        variant(V1_7, 5, 5, 5, 0),     // dark oak planks
+        variant(V1_7, 6, 4, 6, 0),     // acacia sapling
+        variant(V1_7, 6, 5, 6, 0),     // dark oak sapling
+        variant(V1_7, 12, 1, 12, 0),   // red sand
+        variant(V1_7, 38, 1, 38, 0),   // blue orchid
+        variant(V1_7, 38, 2, 38, 0),   // allium
+        variant(V1_7, 38, 3, 38, 0),   // azure bluet
+        variant(V1_7, 38, 4, 38, 0),   // red tulip
+        variant(V1_7, 38, 5, 38, 0),   // orange tulip
+        variant(V1_7, 38, 6, 38, 0),   // white tulip
+        variant(V1_7, 38, 7, 38, 0),   // pink tulip
+        variant(V1_7, 38, 8, 38, 0),   // oxeye daisy
+        variant(V1_7, 97, 3, 97, 2),   // infested mossy stone bricks
+        variant(V1_7, 97, 4, 97, 2),   // infested cracked stone bricks
+        variant(V1_7, 97, 5, 97, 2),   // infested chiseled stone bricks
+        variant(V1_7, 126, 4, 126, 0), // acacia slab
+        variant(V1_7, 126, 5, 126, 0), // dark oak slab
+        variant(V1_7, 349, 1, 349, 0), // raw salmon -> raw fish
+        variant(V1_7, 349, 2, 349, 0), // clownfish -> raw fish
+        variant(V1_7, 349, 3, 349, 0), // pufferfish -> raw fish
+        variant(V1_7, 350, 1, 350, 0), // cooked salmon -> cooked fish
+        item(V1_7, 95, 20, 0),         // stained glass
+        item(V1_7, 160, 102, 0),       // stained glass pane
+        item(V1_7, 161, 18, 0),        // acacia & dark oak leaves -> oak leaves
+        item(V1_7, 162, 17, 0),        // acacia & dark oak log -> oak log
+        item(V1_7, 163, 53, 0),        // acacia stairs -> oak stairs
+        item(V1_7, 164, 53, 0),        // dark oak stairs -> oak stairs
+        item(V1_7, 174, 79, 0),        // packed ice -> ice
+        item(V1_7, 175, 31, 1),        // double plant -> tall grass
+        item(V1_7, 422, 328, 0),       // minecart with command block -> minecart
+        // 1.6
+        keep_damage(V1_6, 159, 35), // stained clay -> wool of the same colour
+        item(V1_6, 170, 35, 4),     // hay bale -> yellow wool
+        keep_damage(V1_6, 171, 35), // carpet -> wool of the same colour
+        item(V1_6, 172, 45, 0),     // hardened clay -> bricks
+        item(V1_6, 173, 35, 15),    // block of coal -> black wool
+        item(V1_6, 417, 265, 0),    // iron horse armor -> iron ingot
+        item(V1_6, 418, 266, 0),    // golden horse armor -> gold ingot
+        item(V1_6, 419, 264, 0),    // diamond horse armor -> diamond
+        item(V1_6, 420, 287, 0),    // lead -> string
+        item(V1_6, 421, 339, 0),    // name tag -> paper
+        // 1.6 spawn eggs
+        variant(V1_6, 383, 100, 383, 90), // horse spawn egg -> pig
+        // 1.5
+        variant(V1_5, 44, 7, 44, 0), // quartz slab -> stone slab
+        item(V1_5, 146, 54, 0),      // trapped chest -> chest
+        item(V1_5, 147, 70, 0),      // light weighted pressure plate
+        item(V1_5, 148, 70, 0),      // heavy weighted pressure plate
+        item(V1_5, 151, 126, 0),     // daylight detector -> oak slab
+        item(V1_5, 152, 35, 14),     // block of redstone -> red wool
+        item(V1_5, 153, 87, 0),      // nether quartz ore -> netherrack
+        item(V1_5, 154, 54, 0),      // hopper -> chest
+        item(V1_5, 155, 80, 0),      // block of quartz -> snow block
+        item(V1_5, 156, 109, 0),     // quartz stairs -> stone brick stairs
+        item(V1_5, 157, 27, 0),      // activator rail -> powered rail
+        item(V1_5, 158, 23, 0),      // dropper -> dispenser
+        item(V1_5, 404, 356, 0),     // comparator -> repeater
+        item(V1_5, 405, 336, 0),     // nether brick -> brick
+        item(V1_5, 406, 337, 0),     // nether quartz -> clay
+        item(V1_5, 407, 328, 0),     // minecart with tnt -> minecart
+        item(V1_5, 408, 342, 0),     // minecart with hopper -> minecart with chest
+        // 1.4.6
+        variant(V1_4_6, 44, 6, 44, 0), // nether brick slab -> stone slab
+        item(V1_4_6, 401, 339, 0),     // firework rocket -> paper
+        item(V1_4_6, 402, 289, 0),     // firework star -> gunpowder
+        item(V1_4_6, 403, 340, 0),     // enchanted book -> book
+    ]
+};
+
+/// Damage values that are block data rather than a variant, like the axis of a log (`17:5`
+/// is a spruce log on its side). Items get them when placed and picked again in creative,
+/// or given with commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlacedDamage {
+    id: i16,
+    /// `None` matches any damage.
+    damage: Option<i16>,
+    /// The bits of the damage that are the variant, unless there's a `variant`.
+    mask: i16,
+    variant: Option<i16>,
+}
+
+/// Any damage of `id`, keeping the bits in `mask`.
+const fn masked(id: i16, mask: i16) -> PlacedDamage {
+    PlacedDamage {
+        id,
+        damage: None,
+        mask,
+        variant: None,
+    }
+}
+
+/// Only `id:damage`, which is `variant`.
+const fn placed(id: i16, damage: i16, variant: i16) -> PlacedDamage {
+    PlacedDamage {
+        id,
+        damage: Some(damage),
+        mask: 0,
+        variant: Some(variant),
+    }
+}
+
+const PLACED_DAMAGES: &[PlacedDamage] = &[
+    masked(6, 7),      // sapling growth stage
+    masked(17, 3),     // log axis
+    masked(18, 3),     // leaves decay
+    masked(44, 7),     // upper slab
+    masked(126, 7),    // upper wooden slab
+    placed(155, 3, 2), // quartz pillar along x
+    placed(155, 4, 2), // quartz pillar along z
+    masked(161, 3),    // leaves2 decay
+    masked(162, 3),    // log2 axis
+    masked(175, 7),    // upper double plant
+    masked(182, 7),    // upper red sandstone slab
+];
+
+/// The variant of `id` a damage in [`PLACED_DAMAGES`] is, any other damage is kept.
+fn placed_variant(id: i16, damage: i16) -> i16 {
+    PLACED_DAMAGES
+        .iter()
+        .find(|placed| placed.id == id && placed.damage.is_none_or(|d| d == damage))
+        .map_or(damage, |placed| {
+            placed.variant.unwrap_or(damage & placed.mask)
+        })
+}
+
+/// The item substitutions that apply to one target version.
+#[derive(Clone)]
+pub struct ItemMap {
+    substitutions: Vec<ItemSubstitution>,
+}
+
+impl ItemMap {
+    pub fn new(target: MinecraftVersion, substitutions: &[ItemSubstitution]) -> Self {
+        Self {
+            substitutions: substitutions
+                .iter()
+                .filter(|substitution| target < substitution.since)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// What `id:damage` becomes, following chains of substitutions like
+    /// [`crate::blocks::BlockMap`] does. Block data kept in the damage is first turned into
+    /// the variant it belongs to, see [`PLACED_DAMAGES`].
+    pub fn get(&self, mut id: i16, mut damage: i16) -> (i16, i16) {
+        damage = placed_variant(id, damage);
+
+        for _ in 0..self.substitutions.len() {
+            let Some(substitution) = self.substitutions.iter().find(|substitution| {
+                substitution.id == id && substitution.damage.is_none_or(|d| d == damage)
+            }) else {
+                break;
+            };
+
+            id = substitution.replacement_id;
+            damage = substitution.replacement_damage.unwrap_or(damage);
+        }
+
+        (id, damage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::MinecraftVersion::*;
+
+    fn get(target: MinecraftVersion, id: i16, damage: i16) -> (i16, i16) {
+        ItemMap::new(target, DEFAULT_SUBSTITUTIONS).get(id, damage)
+    }
+
+    #[test]
+    fn fish_and_golden_apples() {
+        assert_eq!(get(V1_7_10, 349, 2), (349, 2));
+        assert_eq!(get(V1_6_4, 349, 2), (349, 0));
+        assert_eq!(get(V1_6_4, 350, 1), (350, 0));
+
+        // enchanted golden apples are older than every target
+        assert_eq!(get(V1_4_7, 322, 1), (322, 1));
+        assert_eq!(get(V1_4_7, 322, 0), (322, 0));
+    }
+
+    #[test]
+    fn placed_block_data_becomes_the_variant() {
+        // spruce logs on their side, and acacia and dark oak before they existed
+        assert_eq!(get(V1_7_10, 17, 5), (17, 1));
+        assert_eq!(get(V1_7_10, 17, 13), (17, 1));
+        assert_eq!(get(V1_7_10, 162, 5), (162, 1));
+        assert_eq!(get(V1_6_4, 162, 5), (17, 0));
+
+        assert_eq!(get(V1_5, 155, 3), (155, 2));
+        assert_eq!(get(V1_5, 155, 4), (155, 2));
+        assert_eq!(get(V1_5, 155, 1), (155, 1));
+        assert_eq!(get(V1_4_7, 155, 4), (80, 0));
+
+        // upper quartz and nether brick slabs
+        assert_eq!(get(V1_4_7, 44, 15), (44, 0));
+        assert_eq!(get(V1_4_7, 44, 14), (44, 6));
+
+        // durability isn't block data
+        assert_eq!(get(V1_4_7, 276, 12), (276, 12));
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use color_eyre::eyre::{Context, ContextCompat};
+use fastnbt::Value;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::archive::FileArchive;
+use crate::fixer::{FixContext, FixerKind, FixerRegistry};
+use crate::NbtValueExt;
+
+/// Converts a `level.dat` (or `level.dat_old`), including the singleplayer `Player` in it.
+/// With an `archive`, what changed is recorded there under `file_name`.
+pub fn convert_level_dat(
+    input_path: &Path,
+    output_path: &Path,
+    archive: Option<&mut FileArchive>,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let mut root = read_gzip_nbt(input_path)?;
+    let before = archive.is_some().then(|| root.clone());
+
+    let data = root
+        .get_mut("Data")
+        .and_then(NbtValueExt::as_compound)
+        .wrap_err_with(|| format!("{input_path:?} has no Data compound."))?;
+
+    registry.apply(FixerKind::LevelData, data, ctx)?;
+
+    if let Some(player) = data.get_mut("Player").and_then(NbtValueExt::as_compound) {
+        registry.apply(FixerKind::Player, player, ctx)?;
+    }
+
+    if let (Some(archive), Some(before)) = (archive, before) {
+        let file_name = Path::new(output_path.file_name().unwrap_or_default());
+        archive.record(file_name, file_name, &before, &root);
+    }
+
+    write_gzip_nbt(output_path, &root)
+}
+
+pub fn read_gzip_nbt(path: &Path) -> color_eyre::Result<HashMap<String, Value>> {
+    let mut bytes = vec![];
+    GzDecoder::new(File::open(path)?)
+        .read_to_end(&mut bytes)
+        .wrap_err_with(|| format!("Failed to decompress {path:?}."))?;
+
+    Ok(fastnbt::from_bytes(&bytes)?)
+}
+
+pub fn write_gzip_nbt(path: &Path, root: &HashMap<String, Value>) -> color_eyre::Result<()> {
+    let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    encoder.write_all(&fastnbt::to_bytes(root)?)?;
+    encoder.finish()?;
+
+    Ok(())
+}
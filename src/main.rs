@@ -1,465 +1,364 @@
 #![allow(dead_code)]
 
-use std::fs::{self, File};
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::thread;
 
-use clap::Parser;
-use color_eyre::eyre::Context;
-use fastanvil::Region;
+use clap::{Args, Parser, Subcommand};
+use color_eyre::eyre::bail;
 use fastnbt::Value;
-use tracing::warn;
-
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+use crate::chunk_fingerprint::{FingerprintFormat, VersionFilter};
+use crate::fixer::{FixContext, FixerRegistry};
+use crate::mapping::Mapping;
+use crate::region::ChunkErrorPolicy;
+use crate::selection::{BoundingBox, Outside, Selection};
+use crate::unknown::{UnknownIdPolicy, UnknownIds};
+use crate::version::MinecraftVersion;
+use crate::world::{Dimension, WorldOptions};
+
+mod archive;
+mod blocks;
+mod chat;
+mod chunk_fingerprint;
+mod enchantments;
+mod fixer;
+mod fixers;
 mod ids;
+mod item_tag;
+mod items;
+mod level_dat;
+mod mapping;
+mod nibble;
+mod player;
+mod region;
+mod selection;
+mod unknown;
+mod version;
+mod world;
 
 #[derive(Debug, Parser)]
-struct Args {
-    input_world_path: PathBuf,
-    output_world_path: PathBuf,
-}
-
-fn main() -> color_eyre::Result<()> {
-    color_eyre::install()?;
-    tracing_subscriber::fmt::init();
-
-    let args = Args::parse();
-
-    let region_dir_paths = ["region/", "DIM-1/region/", "DIM1/region/"]
-        .iter()
-        .map(|sub_path| {
-            (
-                args.input_world_path.join(sub_path),
-                args.output_world_path.join(sub_path),
-            )
-        });
-
-    for (input_region_dir_path, output_region_dir_path) in region_dir_paths {
-        match fs::read_dir(&input_region_dir_path) {
-            Ok(region_paths) => {
-                fs::create_dir_all(&output_region_dir_path).wrap_err_with(|| {
-                    format!(
-                        "Failed to create output directory at {:?}.",
-                        &output_region_dir_path
-                    )
-                })?;
-
-                for region_path in region_paths {
-                    let region_path = region_path?;
-
-                    let input_region_file = File::open(region_path.path())?;
-                    let output_region_file = File::options()
-                        .read(true)
-                        .write(true)
-                        .create(true)
-                        .truncate(true)
-                        .open(output_region_dir_path.join(region_path.file_name()))?;
-
-                    let input_region = Region::from_stream(input_region_file)?;
-                    let output_region = Region::new(output_region_file)?;
-
-                    downgrade_region(input_region, output_region)?;
-                }
-            }
-            Err(err) => {
-                warn!(
-                    "Failed to read region directory: {:?} - {}, continuing...",
-                    input_region_dir_path, err
-                );
-            }
-        }
-    }
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    Ok(())
+    #[command(flatten)]
+    downgrade: Option<DowngradeArgs>,
 }
 
-fn downgrade_region(mut input: Region<File>, mut output: Region<File>) -> color_eyre::Result<()> {
-    for chunk_data in input.iter() {
-        let chunk_data = chunk_data?;
-        let mut chunk: HashMap<String, Value> = fastnbt::from_bytes(&chunk_data.data)?;
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Convert a world to an older version, the default when no command is given
+    Downgrade(DowngradeArgs),
 
-        let level = chunk.get_mut("Level").unwrap().as_compound().unwrap();
+    /// Convert a world from an older version to the 1.8 format
+    Upgrade(UpgradeArgs),
 
-        // remove new fields
-        level.remove("V"); // 1.7
-        level.remove("InhabitedTime"); // 1.8
-        level.remove("LightPopulated"); // 1.8
+    /// Put back what a downgrade archived, in place, e.g. after the world was upgraded again
+    Restore(RestoreArgs),
 
-        downgrade_entities(level.get_mut("Entities").unwrap().as_list().unwrap())?;
-        downgrade_tile_entities(level.get_mut("TileEntities").unwrap().as_list().unwrap())?;
+    /// Guess which versions saved each chunk of a world or region file
+    Fingerprint(FingerprintArgs),
+}
 
-        let tile_ticks = level.get_mut("TileTicks").and_then(|t| t.as_list());
-        if let Some(tile_ticks) = tile_ticks {
-            for tile_tick in tile_ticks {
-                let tile_tick = tile_tick.as_compound().unwrap();
+#[derive(Debug, Args)]
+struct DowngradeArgs {
+    // clap can't tell these args were given from the flattened `SelectionArgs`, the group
+    // tells it for `Cli::downgrade`
+    #[arg(group = "DowngradeArgs")]
+    input_world_path: PathBuf,
+    #[arg(group = "DowngradeArgs")]
+    output_world_path: PathBuf,
 
-                if let Value::String(id) = &tile_tick["i"] {
-                    tile_tick.insert("i".into(), Value::Int(ids::new_to_old(id).unwrap() as _));
-                }
-            }
-        }
+    /// Version to downgrade the world to, e.g. 1.7.10 or 1.4.7
+    #[arg(long, default_value = "1.4.7")]
+    target: MinecraftVersion,
 
-        output.write_chunk(
-            chunk_data.x,
-            chunk_data.z,
-            fastnbt::to_bytes(&chunk)?.as_ref(),
-        )?;
-    }
+    /// Skip the fixer with this name, can be given multiple times
+    #[arg(long = "disable-fixer", value_name = "NAME")]
+    disabled_fixers: Vec<String>,
 
-    Ok(())
-}
+    /// usercache.json to name players by, defaults to the one next to the input world
+    #[arg(long)]
+    usercache: Option<PathBuf>,
 
-fn downgrade_entities(entities: &mut Vec<Value>) -> color_eyre::Result<()> {
-    for entity in entities {
-        let entity = entity.as_compound().unwrap();
-        let id = entity["id"].as_str().unwrap().to_string();
+    /// CSV file of `uuid,name` lines to name players by, on top of the usercache
+    #[arg(long, value_name = "CSV")]
+    player_names: Option<PathBuf>,
 
-        if id == "Item" {
-            entity.remove("PickupDelay"); // 1.8
-            entity.remove("Thrower"); // 1.7
-            entity.remove("Owner"); // 1.7
+    /// TOML file overriding the default block and item substitutions
+    #[arg(long, value_name = "TOML")]
+    mapping: Option<PathBuf>,
 
-            downgrade_item_stack(entity.get_mut("Item").unwrap().as_compound().unwrap())?;
-        }
+    /// What to do with ids that have no numeric id: error, drop, replace:<id> or keep
+    #[arg(long, default_value = "error", value_name = "POLICY")]
+    unknown: UnknownIdPolicy,
 
-        // decoration entity (painting & item frame) direction (1.8)
-        if let Some(facing) = entity.get("Facing") {
-            let facing = facing.as_i64().unwrap() as i8;
-            entity.remove("Facing");
-            entity.insert("Direction".into(), Value::Byte(facing));
-
-            match facing {
-                0 => {
-                    let z = entity.remove("TileZ").unwrap().as_i64().unwrap() as i32;
-                    entity.insert("TileZ".into(), Value::Int(z - 1));
-                }
-                1 => {
-                    let x = entity.remove("TileX").unwrap().as_i64().unwrap() as i32;
-                    entity.insert("TileX".into(), Value::Int(x - -1));
-                }
-                2 => {
-                    let z = entity.remove("TileZ").unwrap().as_i64().unwrap() as i32;
-                    entity.insert("TileZ".into(), Value::Int(z - -1));
-                }
-                3 => {
-                    let x = entity.remove("TileX").unwrap().as_i64().unwrap() as i32;
-                    entity.insert("TileX".into(), Value::Int(x - 1));
-                }
-
-                _ => {}
-            }
-
-            // item frame rotation (1.8)
-            if let Some(Value::Byte(item_rotation)) = entity.get_mut("ItemRotation") {
-                *item_rotation /= 2;
-            }
-        }
+    /// What to do with chunks that fail to convert: drop, quarantine:<dir> or copy, which
+    /// leaves them unconverted. Defaults to quarantine:<output world>/quarantine
+    #[arg(long, value_name = "POLICY")]
+    chunk_errors: Option<ChunkErrorPolicy>,
 
-        if id == "Arrow" {
-            entity.remove("life"); // 1.7
+    /// How many region files to convert at once, defaults to the number of CPUs
+    #[arg(long, short)]
+    jobs: Option<NonZeroUsize>,
 
-            // 1.8
-            if let Value::String(in_tile) = &entity["inTile"] {
-                entity.insert(
-                    "inTile".into(),
-                    Value::Byte(ids::new_to_old(in_tile).unwrap() as i8),
-                );
-            }
-        }
+    /// Don't archive what the downgrade removes or rewrites for `restore`
+    #[arg(long)]
+    no_archive: bool,
 
-        // throwable entities
-        if id == "Snowball"
-            || id == "ThrownEnderpearl"
-            || id == "ThrownPotion"
-            || id == "ThrownExpBottle"
-        {
-            // 1.8
-            if let Value::String(id) = &entity["inTile"] {
-                entity.insert(
-                    "inTile".into(),
-                    Value::Byte(ids::new_to_old(id).unwrap() as i8),
-                );
-            }
-        }
+    #[command(flatten)]
+    selection: SelectionArgs,
+}
 
-        // explosive projectile entities
-        if id == "Fireball" || id == "SmallFireball" || id == "WitherSkull" {
-            // 1.8
-            if let Value::String(id) = &entity["inTile"] {
-                entity.insert(
-                    "inTile".into(),
-                    Value::Byte(ids::new_to_old(id).unwrap() as i8),
-                );
-            }
-        }
+#[derive(Debug, Args)]
+struct UpgradeArgs {
+    input_world_path: PathBuf,
+    output_world_path: PathBuf,
 
-        if id == "ThrownPotion" {
-            if let Some(potion) = entity.get_mut("Potion") {
-                downgrade_item_stack(potion.as_compound().unwrap())?;
-            }
-        }
+    /// Skip the fixer with this name, can be given multiple times
+    #[arg(long = "disable-fixer", value_name = "NAME")]
+    disabled_fixers: Vec<String>,
 
-        if id == "ItemFrame" {
-            if let Some(item) = entity.get_mut("Item") {
-                downgrade_item_stack(item.as_compound().unwrap())?;
-            }
-        }
+    /// What to do with chunks that fail to convert: drop, quarantine:<dir> or copy, which
+    /// leaves them unconverted. Defaults to quarantine:<output world>/quarantine
+    #[arg(long, value_name = "POLICY")]
+    chunk_errors: Option<ChunkErrorPolicy>,
 
-        if id == "FallingSand" {
-            // 1.8
-            if let Some(Value::String(id)) = entity.remove("Block") {
-                entity.insert(
-                    "Tile".into(),
-                    Value::Byte(ids::new_to_old(&id).unwrap() as i8),
-                );
-            }
-
-            // 1.5
-            entity.remove("TileEntityData");
-        }
+    /// How many region files to convert at once, defaults to the number of CPUs
+    #[arg(long, short)]
+    jobs: Option<NonZeroUsize>,
 
-        if id == "FireworksRocketEntity" {
-            downgrade_item_stack(
-                entity
-                    .get_mut("FireworksItem")
-                    .unwrap()
-                    .as_compound()
-                    .unwrap(),
-            )?;
-        }
+    #[command(flatten)]
+    selection: SelectionArgs,
+}
 
-        // unify minecarts (1.5)
-        if id == "MinecartRideable" {
-            entity.insert("id".into(), Value::String("Minecart".into()));
-            entity.insert("Type".into(), Value::Int(0));
-        }
+#[derive(Debug, Args)]
+struct SelectionArgs {
+    /// Only convert chunks in this box: x1,z1,x2,z2 in blocks or chunk:x1,z1,x2,z2 in chunks
+    #[arg(long, value_name = "BOX", allow_hyphen_values = true)]
+    bbox: Option<BoundingBox>,
 
-        if id == "MinecartChest" {
-            entity.insert("id".into(), Value::String("Minecart".into()));
-            entity.insert("Type".into(), Value::Int(1));
+    /// Only convert this dimension: overworld, nether, end or DIM<n>, can be given multiple times
+    #[arg(long = "dimension", value_name = "DIMENSION")]
+    dimensions: Vec<Dimension>,
 
-            let items = entity.get_mut("Items").unwrap().as_list().unwrap();
-            for item in items {
-                downgrade_item_stack(item.as_compound().unwrap())?;
-            }
-        }
+    /// Only convert these region files, e.g. r.0.0.mca,r.-1.0.mca or DIM-1/region/r.0.0.mca
+    #[arg(long, value_name = "FILES", value_delimiter = ',')]
+    regions: Vec<String>,
 
-        if id == "MinecartFurnace" {
-            entity.insert("id".into(), Value::String("Minecart".into()));
-            entity.insert("Type".into(), Value::Int(2));
-        }
+    /// What to do with the chunks and regions that aren't converted
+    #[arg(long, value_enum, default_value_t)]
+    outside: Outside,
+}
 
-        // mob entities
-        if id == "Mob"
-            || id == "Monster"
-            || id == "Creeper"
-            || id == "Skeleton"
-            || id == "Spider"
-            || id == "Giant"
-            || id == "Zombie"
-            || id == "Slime"
-            || id == "Ghast"
-            || id == "PigZombie"
-            || id == "Enderman"
-            || id == "CaveSpider"
-            || id == "Silverfish"
-            || id == "Blaze"
-            || id == "LavaSlime"
-            || id == "EnderDragon"
-            || id == "WitherBoss"
-            || id == "Bat"
-            || id == "Witch"
-            || id == "Pig"
-            || id == "Sheep"
-            || id == "Cow"
-            || id == "Chicken"
-            || id == "Squid"
-            || id == "Wolf"
-            || id == "MushroomCow"
-            || id == "SnowMan"
-            || id == "Ozelot"
-            || id == "VillagerGolem"
-            || id == "Villager"
-        {
-            // living entity
-            entity.remove("HurtByTimestamp"); // 1.8
-            entity.remove("HealF"); // 1.6
-            entity.remove("Attributes"); // 1.6
-            entity.remove("AbsorptionAmount"); // 1.6
-
-            // 1.8
-            if let Some(active_effects) = entity.get_mut("ActiveEffects").and_then(|e| e.as_list())
-            {
-                for effect in active_effects {
-                    effect.as_compound().unwrap().remove("ShowParticles");
-                }
-            }
-
-            // mob entity
-            for equipment in entity.get_mut("Equipment").unwrap().as_list().unwrap() {
-                downgrade_item_stack(equipment.as_compound().unwrap())?;
-            }
-
-            // 1.6
-            entity.remove("Leashed"); // 1.6
-            entity.remove("Leash"); // 1.6
-            entity.remove("NoAI"); // 1.8
+impl SelectionArgs {
+    fn selection(&self) -> Selection {
+        Selection {
+            bbox: self.bbox,
+            dimensions: self.dimensions.clone(),
+            regions: self.regions.clone(),
+            outside: self.outside,
         }
+    }
+}
 
-        if id == "Zombie" || id == "PigZombie" {
-            entity.remove("CanBreakDoors"); // 1.7
-        }
+#[derive(Debug, Args)]
+struct RestoreArgs {
+    world_path: PathBuf,
+}
 
-        if id == "Slime" || id == "LavaSlime" {
-            entity.remove("wasOnGround"); // 1.8
-        }
+#[derive(Debug, Args)]
+struct FingerprintArgs {
+    /// A world folder or a single .mca file
+    path: PathBuf,
 
-        if id == "PigZombie" {
-            entity.remove("HurtBy"); // 1.8
-        }
+    #[arg(long, value_enum, default_value_t)]
+    format: FingerprintFormat,
 
-        // passive entities
-        if id == "Pig"
-            || id == "Sheep"
-            || id == "Cow"
-            || id == "Chicken"
-            || id == "Wolf"
-            || id == "MushroomCow"
-            || id == "Ozelot"
-            || id == "Villager"
-        {
-            entity.remove("ForcedAge"); // 1.8
-        }
+    /// Render one dimension as an image instead, one pixel per chunk coloured by its min version
+    #[arg(long, value_name = "PATH")]
+    png: Option<PathBuf>,
 
-        if id == "Chicken" {
-            entity.remove("EggLayTime"); // 1.8
-            entity.remove("IsChickenJockey"); // 1.7.3 (1.7.5 kinda)
-        }
+    /// The dimension to render with --png: overworld, nether, end or DIM<n>
+    #[arg(long, default_value = "overworld")]
+    dimension: Dimension,
 
-        // tameable entities
-        if id == "Wolf" || id == "Ozelot" {
-            if let Some(owner_id) = entity.remove("OwnerUUID") {
-                entity.insert("Owner".into(), owner_id);
-            }
-        }
+    /// Only chunks whose min version is this or newer
+    #[arg(long, value_name = "VERSION")]
+    min_version: Option<MinecraftVersion>,
 
-        if id == "Villager" {
-            entity.remove("Career"); // 1.8
-            entity.remove("CareerLevel"); // 1.8
-            entity.remove("Willing"); // 1.8
-            entity.remove("Inventory"); // 1.8
-
-            if let Some(offers) = entity.get_mut("Offers").and_then(NbtValueExt::as_compound) {
-                let recipes = offers.get_mut("Recipes").unwrap().as_list().unwrap();
-                for recipe in recipes {
-                    let recipe = recipe.as_compound().unwrap();
-
-                    recipe.remove("rewardExp"); // 1.8
-                    downgrade_item_stack(recipe.get_mut("buy").unwrap().as_compound().unwrap())?;
-                    downgrade_item_stack(recipe.get_mut("sell").unwrap().as_compound().unwrap())?;
-
-                    if let Some(buy2) = recipe.get_mut("buy") {
-                        downgrade_item_stack(buy2.as_compound().unwrap())?;
-                    }
-                }
-            }
-        }
-    }
+    /// Only chunks whose max version is this or older
+    #[arg(long, value_name = "VERSION")]
+    max_version: Option<MinecraftVersion>,
 
-    Ok(())
+    /// Print the matching chunks grouped by region, as a plan of what to regenerate
+    #[arg(long)]
+    plan: bool,
+
+    /// Copy the world or region to PATH without the matching chunks, so they regenerate
+    #[arg(long, value_name = "PATH")]
+    delete_into: Option<PathBuf>,
 }
 
-fn downgrade_tile_entities(tile_entities: &mut Vec<Value>) -> color_eyre::Result<()> {
-    for tile_entity in tile_entities {
-        let tile_entity = tile_entity.as_compound().unwrap();
-        let id = tile_entity["id"].as_str().unwrap().to_string();
+fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+    // the end of run reports are warnings, so show them without RUST_LOG too
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        // stdout is for reports like `fingerprint --format json`
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+
+    match (cli.command, cli.downgrade) {
+        (Some(Command::Downgrade(args)), _) | (None, Some(args)) => downgrade(args),
+        (Some(Command::Upgrade(args)), _) => upgrade(args),
+        (Some(Command::Restore(args)), _) => restore(args),
+        (Some(Command::Fingerprint(args)), _) => fingerprint(args),
+        // the world paths are required without a command
+        (None, None) => unreachable!(),
+    }
+}
 
-        tile_entity.remove("CustomName"); // 1.5
+fn downgrade(args: DowngradeArgs) -> color_eyre::Result<()> {
+    let mut registry = FixerRegistry::with_defaults();
+    for name in &args.disabled_fixers {
+        registry.disable(name)?;
+    }
 
-        // inventories
-        if id == "Furnace" || id == "Chest" || id == "Trap" || id == "Cauldron" {
-            let items = tile_entity.get_mut("Items").unwrap().as_list().unwrap();
-            for item in items {
-                downgrade_item_stack(item.as_compound().unwrap())?;
-            }
-        }
+    let mapping = match &args.mapping {
+        Some(mapping_path) => Mapping::read(mapping_path)?,
+        None => Mapping::default(),
+    };
+    let mut ctx = FixContext::with_mapping(args.target, &mapping);
+    ctx.unknown_ids = UnknownIds::new(args.unknown);
+
+    let usercache_path = args.usercache.clone().or_else(|| {
+        let path = args.input_world_path.parent()?.join("usercache.json");
+        path.exists().then_some(path)
+    });
+    if let Some(usercache_path) = usercache_path {
+        ctx.player_names
+            .extend(player::read_usercache(&usercache_path)?);
+    }
+    if let Some(player_names_path) = &args.player_names {
+        ctx.player_names
+            .extend(player::read_player_names_csv(player_names_path)?);
+    }
 
-        if id == "Furnace" {
-            tile_entity.remove("CookTimeTotal"); // 1.8
-        }
+    world::convert_world(
+        &args.input_world_path,
+        &args.output_world_path,
+        &WorldOptions {
+            chunk_errors: chunk_errors(&args.chunk_errors, &args.output_world_path),
+            jobs: jobs(args.jobs),
+            archive: !args.no_archive,
+            selection: args.selection.selection(),
+        },
+        &registry,
+        &mut ctx,
+    )?;
+
+    ctx.unknown_ids.report();
 
-        if id == "RecordPlayer" {
-            if let Some(item) = tile_entity
-                .get_mut("RecordItem")
-                .and_then(NbtValueExt::as_compound)
-            {
-                downgrade_item_stack(item)?;
-            }
-        }
+    Ok(())
+}
 
-        // lockable container
-        if id == "Trap" || id == "Cauldron" {
-            tile_entity.remove("Lock"); // 1.8
-        }
+fn upgrade(args: UpgradeArgs) -> color_eyre::Result<()> {
+    let mut registry = FixerRegistry::with_upgrades();
+    for name in &args.disabled_fixers {
+        registry.disable(name)?;
+    }
 
-        if id == "Sign" {
-            downgrade_sign_text(tile_entity, "Text1")?;
-            downgrade_sign_text(tile_entity, "Text2")?;
-            downgrade_sign_text(tile_entity, "Text3")?;
-            downgrade_sign_text(tile_entity, "Text4")?;
-        }
+    let mut ctx = FixContext::new(MinecraftVersion::V1_8);
+
+    world::convert_world(
+        &args.input_world_path,
+        &args.output_world_path,
+        &WorldOptions {
+            chunk_errors: chunk_errors(&args.chunk_errors, &args.output_world_path),
+            jobs: jobs(args.jobs),
+            // there's nothing for `restore` to put back
+            archive: false,
+            selection: args.selection.selection(),
+        },
+        &registry,
+        &mut ctx,
+    )
+}
 
-        if id == "Control" {
-            tile_entity.remove("SuccessCount"); // 1.7
-            tile_entity.remove("TrackOutput"); // 1.7
-            tile_entity.remove("LastOutput"); // 1.7
-            tile_entity.remove("CommandStats"); // 1.7
-        }
+/// Failed chunks are quarantined in the output world unless asked otherwise, copying them
+/// would leave chunks in the old format that can crash the game.
+fn chunk_errors(
+    chunk_errors: &Option<ChunkErrorPolicy>,
+    output_world_path: &Path,
+) -> ChunkErrorPolicy {
+    chunk_errors
+        .clone()
+        .unwrap_or_else(|| ChunkErrorPolicy::Quarantine(output_world_path.join("quarantine")))
+}
 
-        if id == "Skull" {
-            tile_entity.remove("Owner"); // 1.7.6
-            tile_entity.remove("ExtraType"); // 1.7
-        }
+fn jobs(jobs: Option<NonZeroUsize>) -> usize {
+    jobs.or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get)
+}
 
-        // should probably do mob spawners, but uh
-        // no
+fn restore(args: RestoreArgs) -> color_eyre::Result<()> {
+    let stats = archive::restore_world(&args.world_path)?;
+
+    info!(
+        "Restored {} values in {} chunks and {} files",
+        stats.restored, stats.chunks, stats.files
+    );
+    if stats.conflicts > 0 {
+        warn!(
+            "Kept {} values that were changed since the downgrade",
+            stats.conflicts
+        );
     }
 
     Ok(())
 }
 
-fn downgrade_sign_text(
-    sign: &mut HashMap<String, Value>,
-    text_key: &str,
-) -> color_eyre::Result<()> {
-    let text = sign
-        .remove(text_key)
-        .and_then(|value| value.as_str().map(|s| s.to_owned()))
-        .unwrap_or("".to_owned());
-
-    let text = serde_json::from_str::<serde_json::Value>(&text)
-        .map(|v| match v {
-            serde_json::Value::Null => "".to_string(),
-            serde_json::Value::String(string) => string,
-            serde_json::Value::Number(number) => number.to_string(),
+fn fingerprint(args: FingerprintArgs) -> color_eyre::Result<()> {
+    let filter = VersionFilter {
+        min_version: args.min_version,
+        max_version: args.max_version,
+    };
+    if args.delete_into.is_some() && filter.is_empty() {
+        bail!("--delete-into needs --min-version or --max-version to pick the chunks.");
+    }
 
-            other => unimplemented!("sign text: {other:?}"),
-        })
-        .unwrap_or(text);
+    let mut fingerprints = chunk_fingerprint::fingerprint_path(&args.path)?;
 
-    sign.insert(text_key.into(), Value::String(text));
+    if let Some(output_path) = &args.delete_into {
+        let plan = chunk_fingerprint::plan(&fingerprints, &filter);
+        let deleted = chunk_fingerprint::delete_planned_chunks(&args.path, output_path, &plan)?;
+        info!(
+            "Copied {:?} to {output_path:?} without {deleted} chunks",
+            args.path
+        );
+    }
 
-    Ok(())
-}
+    fingerprints.retain(|fingerprint| filter.matches(fingerprint));
 
-fn downgrade_item_stack(item_stack: &mut HashMap<String, Value>) -> color_eyre::Result<()> {
-    // 1.8
-    if let Some(Value::String(ident)) = item_stack.get("id") {
-        item_stack.insert("id".into(), Value::Short(ids::new_to_old(ident).unwrap()));
+    if args.plan {
+        let plan = chunk_fingerprint::plan(&fingerprints, &filter);
+        return chunk_fingerprint::print_plan(&plan, args.format);
     }
 
-    // maybe some things on tag im missing
-    // let tag = item_stack.get_mut("tag").unwrap().as_compound().unwrap();
-
-    Ok(())
+    match &args.png {
+        Some(png_path) => chunk_fingerprint::write_png(&fingerprints, &args.dimension, png_path),
+        None => chunk_fingerprint::print_fingerprints(&fingerprints, args.format),
+    }
 }
 
 trait NbtValueExt {
@@ -482,3 +381,27 @@ impl NbtValueExt for Value {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downgrade_is_the_default_command() {
+        let cli = Cli::try_parse_from(["pv1_dfu", "in", "out", "--target", "1.7.10"]).unwrap();
+        assert!(cli.command.is_none());
+        let args = cli.downgrade.unwrap();
+        assert_eq!(args.input_world_path, Path::new("in"));
+        assert_eq!(args.output_world_path, Path::new("out"));
+        assert_eq!(args.target, MinecraftVersion::V1_7_10);
+
+        let cli = Cli::try_parse_from(["pv1_dfu", "downgrade", "in", "out", "--bbox", "0,0,1,1"])
+            .unwrap();
+        assert!(cli.downgrade.is_none());
+        let Some(Command::Downgrade(args)) = cli.command else {
+            panic!("Expected the downgrade command, got {:?}.", cli.command);
+        };
+        assert_eq!(args.input_world_path, Path::new("in"));
+        assert!(args.selection.bbox.is_some());
+    }
+}
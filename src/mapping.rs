@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::{bail, eyre, Context};
+use serde::Deserialize;
+
+use crate::blocks::{self, BlockSubstitution};
+use crate::ids;
+use crate::items::{self, ItemSubstitution};
+use crate::version::MinecraftVersion;
+
+/// A `--mapping` file as written, e.g.
+///
+/// ```toml
+/// [blocks]
+/// "minecraft:prismarine" = "minecraft:cobblestone"
+/// "minecraft:stone:3" = "minecraft:stone:0"
+///
+/// [items]
+/// "minecraft:prismarine" = "minecraft:cobblestone"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MappingFile {
+    #[serde(default)]
+    blocks: HashMap<String, String>,
+    #[serde(default)]
+    items: HashMap<String, String>,
+}
+
+/// The default substitutions with a mapping file's overrides in front of them.
+#[derive(Debug, Clone)]
+pub struct Mapping {
+    pub blocks: Vec<BlockSubstitution>,
+    pub items: Vec<ItemSubstitution>,
+}
+
+impl Default for Mapping {
+    fn default() -> Self {
+        Self {
+            blocks: blocks::DEFAULT_SUBSTITUTIONS.to_vec(),
+            items: items::DEFAULT_SUBSTITUTIONS.to_vec(),
+        }
+    }
+}
+
+impl Mapping {
+    /// Reads and resolves a mapping file, so a typo fails before anything is converted.
+    ///
+    /// Keys and values are names from [`ids::new_to_old`] or numeric ids, optionally
+    /// followed by `:data` (or `:damage` for items), or variant names from
+    /// [`ids::name_to_variant`]. A key without data matches every data
+    /// value. A block value without it gets 0 plus the data bits the default kept, an item
+    /// value keeps the damage. An override applies to the same targets as the
+    /// default it replaces, or to every target if there is no default for it.
+    pub fn read(path: &Path) -> color_eyre::Result<Self> {
+        let file: MappingFile = toml::from_str(&fs::read_to_string(path)?)
+            .wrap_err_with(|| format!("Failed to parse mapping at {path:?}."))?;
+
+        let mut mapping = Self::default();
+
+        let mut block_overrides = vec![];
+        for (key, value) in sorted(&file.blocks) {
+            let substitution = block_substitution(key, value)
+                .wrap_err_with(|| format!("Invalid block mapping {key:?} in {path:?}."))?;
+            block_overrides.push(substitution);
+        }
+        mapping.blocks.splice(0..0, block_overrides);
+
+        let mut item_overrides = vec![];
+        for (key, value) in sorted(&file.items) {
+            let substitution = item_substitution(key, value)
+                .wrap_err_with(|| format!("Invalid item mapping {key:?} in {path:?}."))?;
+            item_overrides.push(substitution);
+        }
+        mapping.items.splice(0..0, item_overrides);
+
+        Ok(mapping)
+    }
+}
+
+/// Entries with data before the ones without, so `stone:3` wins over `stone`.
+fn sorted(entries: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by_key(|(key, _)| (!key_has_data(key), key.to_string()));
+    entries
+}
+
+fn key_has_data(key: &str) -> bool {
+    parse_id(key, lookup_item).is_ok_and(|(_, data)| data.is_some())
+}
+
+fn block_substitution(key: &str, value: &str) -> color_eyre::Result<BlockSubstitution> {
+    let (id, data) = parse_block(key)?;
+    let (replacement_id, replacement_data) = parse_block(value)?;
+
+    let (data_mask, data) = match data {
+        Some(data) => (0xf, data),
+        None => (0, 0),
+    };
+
+    let default = blocks::DEFAULT_SUBSTITUTIONS
+        .iter()
+        .find(|default| default.id == id && data & default.data_mask == default.data);
+
+    Ok(BlockSubstitution {
+        since: default.map_or(MinecraftVersion::NEWEST, |default| default.since),
+        id,
+        data_mask,
+        data,
+        replacement_id,
+        replacement_data: replacement_data.unwrap_or(0),
+        // without data it keeps what the default kept, like the facing of a fence gate
+        keep_data: match replacement_data {
+            Some(_) => 0,
+            None => default.map_or(0, |default| default.keep_data),
+        },
+    })
+}
+
+fn item_substitution(key: &str, value: &str) -> color_eyre::Result<ItemSubstitution> {
+    let (id, damage) = parse_id(key, lookup_item)?;
+    let (replacement_id, replacement_damage) = parse_id(value, lookup_item)?;
+
+    let since = items::DEFAULT_SUBSTITUTIONS
+        .iter()
+        .find(|default| {
+            default.id == id
+                && (damage.is_none() || default.damage.is_none_or(|d| Some(d) == damage))
+        })
+        .map_or(MinecraftVersion::NEWEST, |default| default.since);
+
+    Ok(ItemSubstitution {
+        since,
+        id,
+        damage,
+        replacement_id,
+        replacement_damage,
+    })
+}
+
+fn parse_block(name: &str) -> color_eyre::Result<(u16, Option<u8>)> {
+    let (id, data) = parse_id(name, lookup_block)?;
+
+    if !(0..4096).contains(&id) {
+        bail!("Block id {id} is out of range.");
+    }
+    let is_name = name.starts_with("minecraft:") || ids::name_to_variant(name).is_some();
+    if is_name && id >= 256 {
+        bail!("{name:?} is an item, not a block.");
+    }
+
+    let data = data
+        .map(|data| {
+            u8::try_from(data)
+                .ok()
+                .filter(|data| *data < 16)
+                .ok_or_else(|| eyre!("Block data {data} is out of range."))
+        })
+        .transpose()?;
+
+    Ok((id as u16, data))
+}
+
+/// `name`, `name:data`, `id`, `id:data` or a variant name like `red_wool`.
+fn parse_id(name: &str, lookup: fn(&str) -> Option<i16>) -> color_eyre::Result<(i16, Option<i16>)> {
+    if let Some(id) = lookup(name) {
+        return Ok((id, None));
+    }
+
+    if let Some((id, data)) = ids::name_to_variant(name) {
+        return Ok((id, Some(data)));
+    }
+
+    if let Some((name, data)) = name.rsplit_once(':') {
+        if let (Some(id), Ok(data)) = (lookup(name), data.parse()) {
+            return Ok((id, Some(data)));
+        }
+    }
+
+    bail!("Unknown id {name:?}.")
+}
+
+fn lookup_item(name: &str) -> Option<i16> {
+    ids::new_to_old(name).or_else(|| name.parse().ok())
+}
+
+fn lookup_block(name: &str) -> Option<i16> {
+    ids::block_new_to_old(name).or_else(|| name.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn read(name: &str, toml: &str) -> color_eyre::Result<Mapping> {
+        let path: PathBuf = std::env::temp_dir().join(format!(
+            "pv1_dfu-mapping-{}-{name}.toml",
+            std::process::id()
+        ));
+        fs::write(&path, toml)?;
+        let mapping = Mapping::read(&path);
+        fs::remove_file(&path)?;
+        mapping
+    }
+
+    fn error(name: &str, toml: &str) -> String {
+        format!("{:?}", read(name, toml).unwrap_err())
+    }
+
+    #[test]
+    fn overrides_come_first() {
+        let mapping = read(
+            "overrides",
+            r#"
+            [blocks]
+            "minecraft:prismarine" = "minecraft:cobblestone"
+            "minecraft:stone:3" = "minecraft:stone:0"
+
+            [items]
+            "minecraft:prismarine" = "red_wool"
+            "#,
+        )
+        .unwrap();
+
+        let defaults = Mapping::default();
+        assert_eq!(mapping.blocks.len(), defaults.blocks.len() + 2);
+        // with data first, and the version of the default it overrides
+        assert_eq!((mapping.blocks[0].id, mapping.blocks[0].data), (1, 3));
+        assert_eq!(mapping.blocks[0].since, MinecraftVersion::V1_8);
+        assert_eq!(
+            (mapping.blocks[1].id, mapping.blocks[1].replacement_id),
+            (168, 4)
+        );
+        assert_eq!(mapping.blocks[1].data_mask, 0);
+
+        let item = mapping.items[0];
+        assert_eq!(
+            (item.id, item.replacement_id, item.replacement_damage),
+            (168, 35, Some(14))
+        );
+    }
+
+    #[test]
+    fn values_without_data_keep_it() {
+        let mapping = read(
+            "keep",
+            r#"
+            [blocks]
+            "minecraft:spruce_fence_gate" = "minecraft:fence_gate"
+            "minecraft:red_sandstone" = "minecraft:sandstone:1"
+
+            [items]
+            "minecraft:red_sandstone" = "minecraft:sandstone"
+            "minecraft:prismarine" = "minecraft:stone:0"
+            "#,
+        )
+        .unwrap();
+
+        let block = |id| mapping.blocks.iter().find(|block| block.id == id).unwrap();
+        assert_eq!(
+            (block(183).replacement_id, block(183).keep_data),
+            (107, 0xf)
+        );
+        assert_eq!((block(179).replacement_data, block(179).keep_data), (1, 0));
+
+        let item = |id| mapping.items.iter().find(|item| item.id == id).unwrap();
+        assert_eq!(item(179).replacement_damage, None);
+        assert_eq!(item(168).replacement_damage, Some(0));
+    }
+
+    #[test]
+    fn invalid_mappings_fail() {
+        assert!(error(
+            "unknown-id",
+            "[blocks]\n\"minecraft:nope\" = \"minecraft:stone\""
+        )
+        .contains("Unknown id \"minecraft:nope\""));
+        // item names aren't looked up as blocks at all
+        assert!(error(
+            "item-block",
+            "[blocks]\n\"minecraft:slime\" = \"minecraft:diamond\""
+        )
+        .contains("Unknown id \"minecraft:diamond\""));
+        assert!(error(
+            "item-variant",
+            "[blocks]\n\"minecraft:slime\" = \"lapis_lazuli\""
+        )
+        .contains("is an item, not a block"));
+        assert!(error(
+            "data",
+            "[blocks]\n\"minecraft:stone:16\" = \"minecraft:stone\""
+        )
+        .contains("Block data 16 is out of range"));
+        assert!(error("block-id", "[blocks]\n\"4096\" = \"1\"").contains("out of range"));
+        assert!(error("section", "[entities]\n\"Zombie\" = \"Pig\"").contains("Failed to parse"));
+    }
+}
@@ -0,0 +1,74 @@
+/// Packs nibbles into bytes, low half first, the inverse of [`NibbleIter`].
+pub fn pack_nibbles(nibbles: impl IntoIterator<Item = u8>) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut nibbles = nibbles.into_iter();
+
+    while let Some(low) = nibbles.next() {
+        let high = nibbles.next().unwrap_or(0);
+        bytes.push((low & 0xf) | (high << 4));
+    }
+
+    bytes
+}
+
+/// Splits bytes into nibbles, low half first, the way Data, Add and the light arrays are
+/// packed.
+pub struct NibbleIter<I>
+where
+    I: Iterator<Item = u8>,
+{
+    inner: I,
+    last: u8,
+    last_lsb: bool,
+}
+
+impl<I> NibbleIter<I>
+where
+    I: Iterator<Item = u8>,
+{
+    pub fn from_iter(inner: I) -> Self {
+        Self {
+            inner,
+            last: 0,
+            last_lsb: false,
+        }
+    }
+}
+
+impl<I> Iterator for NibbleIter<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = if !self.last_lsb {
+            self.last = self.inner.next()?;
+            self.last & 0xf
+        } else {
+            self.last >> 4
+        };
+
+        self.last_lsb = !self.last_lsb;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nibbles_round_trip() {
+        let nibbles: Vec<u8> = (0..16).chain([15, 0, 7]).collect();
+
+        let bytes = pack_nibbles(nibbles.iter().copied());
+        assert_eq!(bytes[0], 0x10);
+        assert_eq!(bytes.len(), 10);
+
+        let unpacked: Vec<u8> = NibbleIter::from_iter(bytes.into_iter()).collect();
+        // an odd count is padded with a 0 nibble
+        assert_eq!(unpacked[..nibbles.len()], nibbles[..]);
+        assert_eq!(unpacked[nibbles.len()..], [0]);
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::{bail, Context};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::archive::FileArchive;
+use crate::fixer::{FixContext, FixerKind, FixerRegistry};
+use crate::level_dat::{read_gzip_nbt, write_gzip_nbt};
+use crate::version::MinecraftVersion;
+
+#[derive(Debug, Deserialize)]
+struct UserCacheEntry {
+    name: String,
+    uuid: String,
+}
+
+/// Reads the `usercache.json` a 1.7.6+ server keeps next to its worlds, as uuid to name.
+pub fn read_usercache(path: &Path) -> color_eyre::Result<HashMap<String, String>> {
+    let entries: Vec<UserCacheEntry> = serde_json::from_str(&fs::read_to_string(path)?)
+        .wrap_err_with(|| format!("Failed to parse usercache at {path:?}."))?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| (normalize_uuid(&entry.uuid), entry.name))
+        .collect())
+}
+
+/// Reads `uuid,name` lines, as uuid to name. A header line and blank lines are skipped.
+pub fn read_player_names_csv(path: &Path) -> color_eyre::Result<HashMap<String, String>> {
+    let mut names = HashMap::new();
+
+    for (line_number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (line_number == 0 && line.to_lowercase().starts_with("uuid")) {
+            continue;
+        }
+
+        let Some((uuid, name)) = line.split_once(',') else {
+            bail!(
+                "{path:?}:{}: expected `uuid,name`, got {line:?}.",
+                line_number + 1
+            );
+        };
+
+        names.insert(normalize_uuid(uuid), name.trim().to_string());
+    }
+
+    Ok(names)
+}
+
+/// Lowercase with dashes, which is how playerdata files are named.
+pub fn normalize_uuid(uuid: &str) -> String {
+    let uuid = uuid.trim().to_lowercase();
+    if uuid.len() == 32 && !uuid.contains('-') {
+        format!(
+            "{}-{}-{}-{}-{}",
+            &uuid[0..8],
+            &uuid[8..12],
+            &uuid[12..16],
+            &uuid[16..20],
+            &uuid[20..32]
+        )
+    } else {
+        uuid
+    }
+}
+
+/// Converts `playerdata/<uuid>.dat` and `players/<name>.dat`. Before 1.7.6 players were
+/// stored by name, so uuid keyed files are renamed with `ctx.player_names` for those targets.
+/// With an `archive`, what changed and the renames are recorded there.
+pub fn convert_players(
+    input_world_path: &Path,
+    output_world_path: &Path,
+    mut archive: Option<&mut FileArchive>,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    let by_name = ctx.target < MinecraftVersion::V1_7_6;
+
+    for (input_dir, keyed_by_uuid) in [("playerdata", true), ("players", false)] {
+        let Ok(player_paths) = fs::read_dir(input_world_path.join(input_dir)) else {
+            continue;
+        };
+
+        for player_path in player_paths {
+            let player_path = player_path?.path();
+            if player_path
+                .extension()
+                .is_none_or(|extension| extension != "dat")
+            {
+                continue;
+            }
+
+            let stem = player_path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+
+            let output_path = if keyed_by_uuid && by_name {
+                let Some(name) = ctx.player_names.get(&normalize_uuid(&stem)) else {
+                    warn!(
+                        "No name known for player {stem}, skipping {player_path:?}. Pass \
+                         --usercache or --player-names to include it."
+                    );
+                    continue;
+                };
+
+                output_world_path
+                    .join("players")
+                    .join(format!("{name}.dat"))
+            } else {
+                output_world_path
+                    .join(if keyed_by_uuid {
+                        "playerdata"
+                    } else {
+                        "players"
+                    })
+                    .join(player_path.file_name().unwrap())
+            };
+
+            let mut player = read_gzip_nbt(&player_path)?;
+            let before = player.clone();
+            registry.apply(FixerKind::Player, &mut player, ctx)?;
+
+            if let Some(archive) = archive.as_deref_mut() {
+                archive.record(
+                    output_path.strip_prefix(output_world_path)?,
+                    player_path.strip_prefix(input_world_path)?,
+                    &before,
+                    &player,
+                );
+            }
+
+            fs::create_dir_all(output_path.parent().unwrap())?;
+            write_gzip_nbt(&output_path, &player)?;
+
+            info!("Converted player {player_path:?} to {output_path:?}");
+        }
+    }
+
+    Ok(())
+}
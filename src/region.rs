@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use color_eyre::eyre::{bail, Context, OptionExt};
+use fastanvil::Region;
+use fastnbt::Value;
+use tracing::{error, info_span, warn};
+
+use crate::archive::RegionArchive;
+use crate::fixer::{Direction, FixContext, FixerKind, FixerRegistry};
+use crate::selection::{Outside, RegionSelection};
+use crate::NbtValueExt;
+
+/// What to do with a chunk that fails to convert.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkErrorPolicy {
+    /// Write the chunk to the output as it was, in the format of the input.
+    Copy,
+    /// Leave the chunk out, so the game generates it again.
+    Drop,
+    /// Leave the chunk out and save it as `c.<x>.<z>.nbt` in this directory.
+    Quarantine(PathBuf),
+}
+
+impl FromStr for ChunkErrorPolicy {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "copy" => Ok(Self::Copy),
+            "drop" => Ok(Self::Drop),
+            _ => match s.strip_prefix("quarantine:") {
+                Some(dir) if !dir.is_empty() => Ok(Self::Quarantine(dir.into())),
+                _ => bail!("Expected copy, drop or quarantine:<dir>, got {s:?}."),
+            },
+        }
+    }
+}
+
+/// How many chunks were converted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RegionStats {
+    pub regions: usize,
+    pub chunks: usize,
+    pub failed_chunks: usize,
+    /// Failed chunks written to the output unconverted, by [`ChunkErrorPolicy::Copy`].
+    pub copied_chunks: usize,
+    /// Chunks outside the selection, skipped or copied as they were.
+    pub outside_chunks: usize,
+}
+
+impl RegionStats {
+    pub fn merge(&mut self, other: RegionStats) {
+        self.regions += other.regions;
+        self.chunks += other.chunks;
+        self.failed_chunks += other.failed_chunks;
+        self.copied_chunks += other.copied_chunks;
+        self.outside_chunks += other.outside_chunks;
+    }
+}
+
+/// Converts the selected chunks of a region file. With an `archive_path`, everything the
+/// conversion changed is archived there.
+pub fn convert_region_file(
+    input_path: &Path,
+    output_path: &Path,
+    selection: RegionSelection,
+    chunk_errors: &ChunkErrorPolicy,
+    archive_path: Option<&Path>,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<RegionStats> {
+    let _span = info_span!("region", path = ?input_path).entered();
+
+    let input_region_file = File::open(input_path)?;
+    let output_region_file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(output_path)?;
+
+    let input_region = Region::from_stream(input_region_file)?;
+    let output_region = Region::new(output_region_file)?;
+
+    let mut archive = archive_path.map(|_| RegionArchive::default());
+
+    let stats = convert_region(
+        input_region,
+        output_region,
+        selection,
+        chunk_errors,
+        archive.as_mut(),
+        registry,
+        ctx,
+    )?;
+
+    if let (Some(archive), Some(archive_path)) = (archive, archive_path) {
+        if !archive.is_empty() {
+            archive.write(archive_path)?;
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Converts every chunk of a region on its own, so one broken chunk is handled by
+/// `chunk_errors` instead of failing the region. Chunks outside `selection` are skipped or
+/// copied as they are.
+pub fn convert_region(
+    mut input: Region<File>,
+    mut output: Region<File>,
+    selection: RegionSelection,
+    chunk_errors: &ChunkErrorPolicy,
+    mut archive: Option<&mut RegionArchive>,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<RegionStats> {
+    let mut stats = RegionStats {
+        regions: 1,
+        ..Default::default()
+    };
+
+    for z in 0..32 {
+        for x in 0..32 {
+            let data = match input.read_chunk(x, z) {
+                Ok(Some(data)) => data,
+                Ok(None) => continue,
+                Err(err) => {
+                    error!("Failed to read chunk {x}, {z}, leaving it out: {err}");
+                    stats.failed_chunks += 1;
+                    continue;
+                }
+            };
+
+            if !selection.bbox.contains(x as i32, z as i32) {
+                stats.outside_chunks += 1;
+                if selection.outside == Outside::Copy {
+                    output.write_chunk(x, z, &data)?;
+                }
+                continue;
+            }
+
+            stats.chunks += 1;
+
+            match convert_chunk(&data, registry, ctx) {
+                Ok(converted) => {
+                    if let Some(archive) = archive.as_deref_mut() {
+                        archive.record(x, z, &data, &converted)?;
+                    }
+
+                    output.write_chunk(x, z, &converted)?;
+                }
+                Err(err) => {
+                    error!("Failed to convert chunk {x}, {z}: {err:#}");
+                    stats.failed_chunks += 1;
+
+                    match chunk_errors {
+                        ChunkErrorPolicy::Copy => {
+                            output.write_chunk(x, z, &data)?;
+                            stats.copied_chunks += 1;
+                        }
+                        ChunkErrorPolicy::Drop => {}
+                        ChunkErrorPolicy::Quarantine(dir) => {
+                            let path = dir.join(format!("c.{x}.{z}.nbt"));
+                            fs::create_dir_all(dir)?;
+                            fs::write(&path, &data).wrap_err_with(|| {
+                                format!("Failed to quarantine chunk to {path:?}.")
+                            })?;
+
+                            warn!("Quarantined chunk {x}, {z} to {path:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Converts one chunk's uncompressed NBT.
+fn convert_chunk(
+    data: &[u8],
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<Vec<u8>> {
+    let mut chunk: HashMap<String, Value> = fastnbt::from_bytes(data)?;
+    // block substitutions only go down, an upgraded chunk already has every block
+    let downgrade = registry.direction() == Direction::Downgrade;
+
+    let level = chunk
+        .get_mut("Level")
+        .and_then(NbtValueExt::as_compound)
+        .ok_or_eyre("The chunk has no Level compound.")?;
+
+    registry.apply(FixerKind::Level, level, ctx)?;
+
+    let entities = level
+        .get_mut("Entities")
+        .and_then(NbtValueExt::as_list)
+        .ok_or_eyre("Entities isn't a list.")?;
+    for entity in entities.iter_mut() {
+        let entity = entity
+            .as_compound()
+            .ok_or_eyre("An entity isn't a compound.")?;
+
+        fix_entity(entity, registry, ctx, downgrade)?;
+    }
+    entities.retain(|entity| !is_empty_compound(entity));
+
+    let tile_entities = level
+        .get_mut("TileEntities")
+        .and_then(NbtValueExt::as_list)
+        .ok_or_eyre("TileEntities isn't a list.")?;
+    for tile_entity in tile_entities.iter_mut() {
+        let tile_entity = tile_entity
+            .as_compound()
+            .ok_or_eyre("A tile entity isn't a compound.")?;
+
+        registry.apply(FixerKind::TileEntity, tile_entity, ctx)?;
+    }
+    tile_entities.retain(|tile_entity| !is_empty_compound(tile_entity));
+
+    if downgrade {
+        ctx.blocks.downgrade_level(level);
+    }
+
+    Ok(fastnbt::to_bytes(&chunk)?)
+}
+
+/// Fixes an entity and the ones it rides or carries, dropping those the fixers emptied.
+fn fix_entity(
+    entity: &mut HashMap<String, Value>,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+    downgrade: bool,
+) -> color_eyre::Result<()> {
+    registry.apply(FixerKind::Entity, entity, ctx)?;
+    if downgrade {
+        ctx.blocks.downgrade_entity(entity);
+    }
+
+    if let Some(mount) = entity.get_mut("Riding").and_then(NbtValueExt::as_compound) {
+        fix_entity(mount, registry, ctx, downgrade)?;
+        if mount.is_empty() {
+            entity.remove("Riding");
+        }
+    }
+    if let Some(passengers) = entity.get_mut("Passengers").and_then(NbtValueExt::as_list) {
+        for passenger in passengers.iter_mut() {
+            if let Some(passenger) = passenger.as_compound() {
+                fix_entity(passenger, registry, ctx, downgrade)?;
+            }
+        }
+        passengers.retain(|passenger| !is_empty_compound(passenger));
+    }
+
+    Ok(())
+}
+
+fn is_empty_compound(value: &Value) -> bool {
+    matches!(value, Value::Compound(compound) if compound.is_empty())
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use color_eyre::eyre::{bail, eyre};
+
+use crate::world::Dimension;
+
+/// A box of chunks, inclusive on all sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_x: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_z: i32,
+}
+
+impl BoundingBox {
+    /// Every chunk of a region, in coordinates within the region.
+    pub const REGION: Self = Self {
+        min_x: 0,
+        min_z: 0,
+        max_x: 31,
+        max_z: 31,
+    };
+
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_z..=self.max_z).contains(&z)
+    }
+
+    /// The part of the box inside region `region_x, region_z`, in coordinates within the
+    /// region.
+    fn in_region(&self, region_x: i32, region_z: i32) -> Option<Self> {
+        let (offset_x, offset_z) = (region_x * 32, region_z * 32);
+        let bbox = Self {
+            min_x: (self.min_x - offset_x).max(0),
+            min_z: (self.min_z - offset_z).max(0),
+            max_x: (self.max_x - offset_x).min(31),
+            max_z: (self.max_z - offset_z).min(31),
+        };
+
+        (bbox.min_x <= bbox.max_x && bbox.min_z <= bbox.max_z).then_some(bbox)
+    }
+}
+
+/// `x1,z1,x2,z2` in block coordinates, or `chunk:x1,z1,x2,z2` in chunk coordinates. The
+/// corners can be given in any order.
+impl FromStr for BoundingBox {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (coordinates, shift) = match s.strip_prefix("chunk:") {
+            Some(coordinates) => (coordinates, 0),
+            // blocks to chunks
+            None => (s, 4),
+        };
+
+        let coordinates = coordinates
+            .split(',')
+            .map(|coordinate| coordinate.trim().parse::<i32>().map(|c| c >> shift))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| eyre!("Invalid coordinate in bounding box {s:?}: {err}."))?;
+        let [x1, z1, x2, z2] = coordinates[..] else {
+            bail!("Expected x1,z1,x2,z2 or chunk:x1,z1,x2,z2, got {s:?}.");
+        };
+
+        Ok(Self {
+            min_x: x1.min(x2),
+            min_z: z1.min(z2),
+            max_x: x1.max(x2),
+            max_z: z1.max(z2),
+        })
+    }
+}
+
+/// What to do with chunks outside a [`Selection`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Outside {
+    /// Leave them out of the output world.
+    #[default]
+    Skip,
+    /// Copy them to the output world without converting them.
+    Copy,
+}
+
+/// The chunks to convert in one region file, and what happens to the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionSelection {
+    /// In coordinates within the region.
+    pub bbox: BoundingBox,
+    pub outside: Outside,
+}
+
+/// Which chunks of a world to convert. An empty selection selects everything.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub bbox: Option<BoundingBox>,
+    pub dimensions: Vec<Dimension>,
+    /// Region file names like `r.0.0.mca`, or paths relative to the world.
+    pub regions: Vec<String>,
+    pub outside: Outside,
+}
+
+impl Selection {
+    /// The chunks to convert in a region file, given by its path relative to the world, or
+    /// `None` if there are none.
+    pub fn region(&self, relative_path: &Path) -> Option<RegionSelection> {
+        if !self.dimensions.is_empty() && !self.dimensions.contains(&Dimension::of(relative_path)) {
+            return None;
+        }
+
+        if !self.regions.is_empty()
+            && !self.regions.iter().any(|region| {
+                relative_path.file_name() == Some(region.as_ref())
+                    || relative_path == Path::new(region)
+            })
+        {
+            return None;
+        }
+
+        let bbox = match (self.bbox, region_coordinates(relative_path)) {
+            (Some(bbox), Some((region_x, region_z))) => bbox.in_region(region_x, region_z)?,
+            // a region not named like one is converted whole rather than guessed at
+            _ => BoundingBox::REGION,
+        };
+
+        Some(RegionSelection {
+            bbox,
+            outside: self.outside,
+        })
+    }
+}
+
+/// The coordinates in a region file name like `r.-1.2.mca`.
+fn region_coordinates(relative_path: &Path) -> Option<(i32, i32)> {
+    let file_name = relative_path.file_name()?.to_str()?;
+    let mut parts = file_name
+        .strip_prefix("r.")?
+        .strip_suffix(".mca")?
+        .split('.');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(x), Some(z), None) => Some((x.parse().ok()?, z.parse().ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(min_x: i32, min_z: i32, max_x: i32, max_z: i32) -> BoundingBox {
+        BoundingBox {
+            min_x,
+            min_z,
+            max_x,
+            max_z,
+        }
+    }
+
+    #[test]
+    fn bounding_boxes_parse() {
+        // blocks round down to the chunk they're in
+        assert_eq!(
+            "-17, 5, 31, -1".parse::<BoundingBox>().unwrap(),
+            bbox(-2, -1, 1, 0)
+        );
+        assert_eq!(
+            "chunk:3,-4,-2,1".parse::<BoundingBox>().unwrap(),
+            bbox(-2, -4, 3, 1)
+        );
+        assert_eq!(
+            "chunk:-1,-1,-1,-1".parse::<BoundingBox>().unwrap(),
+            bbox(-1, -1, -1, -1)
+        );
+
+        assert!("1,2,3".parse::<BoundingBox>().is_err());
+        assert!("chunk:1,2,3,4,5".parse::<BoundingBox>().is_err());
+        assert!("chunks:1,2,3,4".parse::<BoundingBox>().is_err());
+        assert!("1,2,x,4".parse::<BoundingBox>().is_err());
+    }
+
+    #[test]
+    fn bounding_boxes_split_into_regions() {
+        let selected = bbox(-40, 10, 5, 40);
+
+        assert_eq!(selected.in_region(-2, 0), Some(bbox(24, 10, 31, 31)));
+        assert_eq!(selected.in_region(-1, 1), Some(bbox(0, 0, 31, 8)));
+        assert_eq!(selected.in_region(0, 0), Some(bbox(0, 10, 5, 31)));
+        assert_eq!(selected.in_region(1, 0), None);
+        assert_eq!(selected.in_region(0, -1), None);
+        assert_eq!(selected.in_region(-3, 0), None);
+
+        let selection = Selection {
+            bbox: Some(selected),
+            ..Selection::default()
+        };
+        assert_eq!(
+            selection
+                .region(Path::new("region/r.-1.1.mca"))
+                .map(|region| region.bbox),
+            selected.in_region(-1, 1)
+        );
+        assert_eq!(selection.region(Path::new("region/r.1.0.mca")), None);
+        // not named like a region, so converted whole
+        assert_eq!(
+            selection
+                .region(Path::new("region/backup.mca"))
+                .map(|region| region.bbox),
+            Some(BoundingBox::REGION)
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use color_eyre::eyre::{bail, eyre};
+use tracing::{info, warn};
+
+use crate::ids;
+use crate::version::MinecraftVersion;
+
+/// What to do with a namespaced id that has no numeric id in [`ids::new_to_old`], like a
+/// modded item, or with an id that's newer than the target and has no substitution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownIdPolicy {
+    /// Stop the conversion.
+    #[default]
+    Error,
+    /// Remove whatever holds the id: the item stack, falling block or tile tick. A
+    /// projectile stuck in an unknown block is left stuck in air.
+    Drop,
+    /// Use this numeric id instead, resolved once as an item and once as a block since names
+    /// like `minecraft:bed` are both. `block` is `None` for items without a block form.
+    Replace { item: i16, block: Option<i16> },
+    /// Leave the namespaced id as is.
+    Keep,
+}
+
+impl FromStr for UnknownIdPolicy {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "drop" => Ok(Self::Drop),
+            "keep" => Ok(Self::Keep),
+            _ => {
+                let Some(id) = s.strip_prefix("replace:") else {
+                    bail!("Expected error, drop, replace:<id> or keep, got {s:?}.");
+                };
+
+                let number = id.parse::<i16>().ok();
+                let item = ids::new_to_old(id)
+                    .or(number)
+                    .ok_or_else(|| eyre!("Unknown replacement id {id:?}."))?;
+                let block =
+                    ids::block_new_to_old(id).or(number.filter(|number| (0..256).contains(number)));
+
+                Ok(Self::Replace { item, block })
+            }
+        }
+    }
+}
+
+impl fmt::Display for UnknownIdPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Drop => write!(f, "dropped"),
+            Self::Replace {
+                item,
+                block: Some(block),
+            } if block != item => write!(f, "replaced with {item}, or {block} for blocks"),
+            Self::Replace { item, .. } => write!(f, "replaced with {item}"),
+            Self::Keep => write!(f, "kept"),
+        }
+    }
+}
+
+/// The outcome of looking up a namespaced id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OldId {
+    Id(i16),
+    Drop,
+    Keep,
+}
+
+/// Every unknown id met during a conversion, by where it was found.
+#[derive(Debug, Clone, Default)]
+pub struct UnknownIds {
+    pub policy: UnknownIdPolicy,
+    counts: BTreeMap<(&'static str, String), usize>,
+    /// The counted ids that are known, but too new for the target.
+    too_new: BTreeSet<(&'static str, String)>,
+}
+
+impl UnknownIds {
+    pub fn new(policy: UnknownIdPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// The numeric id of `name`, or what the policy says to do instead. `place` describes
+    /// where the id was found, like "item stack".
+    pub fn old_id(&mut self, name: &str, place: &'static str) -> color_eyre::Result<OldId> {
+        match ids::new_to_old(name) {
+            Some(id) => Ok(OldId::Id(id)),
+            None => self.unknown(name, place, false),
+        }
+    }
+
+    /// Like [`Self::old_id`] for places that only hold blocks.
+    pub fn old_block_id(&mut self, name: &str, place: &'static str) -> color_eyre::Result<OldId> {
+        match ids::block_new_to_old(name) {
+            Some(id) => Ok(OldId::Id(id)),
+            None => self.unknown(name, place, true),
+        }
+    }
+
+    /// What the policy says to do with a numeric id that's newer than `target` and has no
+    /// substitution. [`OldId::Keep`] keeps the numeric id.
+    pub fn too_new(
+        &mut self,
+        id: i16,
+        target: MinecraftVersion,
+        place: &'static str,
+    ) -> color_eyre::Result<OldId> {
+        let name = ids::old_to_new(id).map_or_else(|| id.to_string(), str::to_string);
+
+        if self.policy == UnknownIdPolicy::Error {
+            bail!(
+                "{name:?} in {place} doesn't exist in {target} and has no substitution. Pass \
+                 --mapping to substitute it or --unknown to drop, replace or keep it."
+            );
+        }
+
+        self.too_new.insert((place, name.clone()));
+        self.unknown(&name, place, false)
+    }
+
+    /// `block` is whether `place` only holds blocks.
+    fn unknown(
+        &mut self,
+        name: &str,
+        place: &'static str,
+        block: bool,
+    ) -> color_eyre::Result<OldId> {
+        match self.policy {
+            UnknownIdPolicy::Error => {
+                bail!("Unknown id {name:?} in {place}. Pass --unknown to drop, replace or keep it.")
+            }
+            UnknownIdPolicy::Replace { item, block: None } if block => bail!(
+                "Unknown id {name:?} in {place} can't be replaced with item {item}, which has no \
+                 block. Pass --unknown replace:<block> to use a block instead."
+            ),
+            _ => {}
+        }
+
+        *self.counts.entry((place, name.to_string())).or_default() += 1;
+
+        Ok(match self.policy {
+            UnknownIdPolicy::Error => unreachable!(),
+            UnknownIdPolicy::Drop => OldId::Drop,
+            UnknownIdPolicy::Replace { item, block: None } => OldId::Id(item),
+            UnknownIdPolicy::Replace {
+                item,
+                block: Some(block_id),
+            } => OldId::Id(if block { block_id } else { item }),
+            UnknownIdPolicy::Keep => OldId::Keep,
+        })
+    }
+
+    pub fn merge(&mut self, other: UnknownIds) {
+        for (key, count) in other.counts {
+            *self.counts.entry(key).or_default() += count;
+        }
+        self.too_new.extend(other.too_new);
+    }
+
+    /// Logs how often each unknown id was met.
+    pub fn report(&self) {
+        if self.counts.is_empty() {
+            info!("No unknown ids.");
+            return;
+        }
+
+        for ((place, name), count) in &self.counts {
+            let key = (*place, name.clone());
+            if self.policy == UnknownIdPolicy::Keep && self.too_new.contains(&key) {
+                warn!(
+                    "Kept {name:?} in {place} {count} time(s), which the target doesn't have. \
+                     The game may drop it or fail to load what holds it."
+                );
+                continue;
+            }
+
+            warn!(
+                "Unknown id {name:?} in {place}: {count} time(s), {}.",
+                self.policy
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacements_resolve_blocks_and_items() {
+        assert_eq!(
+            "replace:minecraft:bed".parse::<UnknownIdPolicy>().unwrap(),
+            UnknownIdPolicy::Replace {
+                item: 355,
+                block: Some(26)
+            }
+        );
+        assert_eq!(
+            "replace:minecraft:diamond"
+                .parse::<UnknownIdPolicy>()
+                .unwrap(),
+            UnknownIdPolicy::Replace {
+                item: 264,
+                block: None
+            }
+        );
+        assert_eq!(
+            "replace:1".parse::<UnknownIdPolicy>().unwrap(),
+            UnknownIdPolicy::Replace {
+                item: 1,
+                block: Some(1)
+            }
+        );
+        assert!("replace:minecraft:nope".parse::<UnknownIdPolicy>().is_err());
+    }
+
+    #[test]
+    fn replacements_fit_the_place() {
+        let mut unknown_ids = UnknownIds::new("replace:minecraft:bed".parse().unwrap());
+        assert_eq!(
+            unknown_ids.old_id("mod:thing", "item stack").unwrap(),
+            OldId::Id(355)
+        );
+        assert_eq!(
+            unknown_ids.old_block_id("mod:thing", "tile tick").unwrap(),
+            OldId::Id(26)
+        );
+
+        let mut unknown_ids = UnknownIds::new("replace:minecraft:diamond".parse().unwrap());
+        assert_eq!(
+            unknown_ids.old_id("mod:thing", "item stack").unwrap(),
+            OldId::Id(264)
+        );
+        assert!(unknown_ids.old_block_id("mod:thing", "tile tick").is_err());
+    }
+}
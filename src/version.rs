@@ -0,0 +1,158 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum MinecraftVersion {
+    V1_4_5,
+    V1_4_6,
+    V1_4_7,
+    V1_5,
+    V1_5_1,
+    V1_5_2,
+    V1_6,
+    V1_6_1,
+    V1_6_2,
+    V1_6_3,
+    V1_6_4,
+    V1_7,
+    V1_7_1,
+    V1_7_2,
+    V1_7_3,
+    V1_7_4,
+    V1_7_5,
+    V1_7_6,
+    V1_7_7,
+    V1_7_8,
+    V1_7_10,
+    V1_8,
+    V1_8_1,
+    V1_8_2,
+    V1_8_3,
+    V1_8_4,
+    V1_8_5,
+    V1_8_6,
+    V1_8_7,
+    V1_8_8,
+    V1_8_9,
+    V1_9_4,
+}
+
+impl MinecraftVersion {
+    pub const ALL: [MinecraftVersion; 32] = [
+        MinecraftVersion::V1_4_5,
+        MinecraftVersion::V1_4_6,
+        MinecraftVersion::V1_4_7,
+        MinecraftVersion::V1_5,
+        MinecraftVersion::V1_5_1,
+        MinecraftVersion::V1_5_2,
+        MinecraftVersion::V1_6,
+        MinecraftVersion::V1_6_1,
+        MinecraftVersion::V1_6_2,
+        MinecraftVersion::V1_6_3,
+        MinecraftVersion::V1_6_4,
+        MinecraftVersion::V1_7,
+        MinecraftVersion::V1_7_1,
+        MinecraftVersion::V1_7_2,
+        MinecraftVersion::V1_7_3,
+        MinecraftVersion::V1_7_4,
+        MinecraftVersion::V1_7_5,
+        MinecraftVersion::V1_7_6,
+        MinecraftVersion::V1_7_7,
+        MinecraftVersion::V1_7_8,
+        MinecraftVersion::V1_7_10,
+        MinecraftVersion::V1_8,
+        MinecraftVersion::V1_8_1,
+        MinecraftVersion::V1_8_2,
+        MinecraftVersion::V1_8_3,
+        MinecraftVersion::V1_8_4,
+        MinecraftVersion::V1_8_5,
+        MinecraftVersion::V1_8_6,
+        MinecraftVersion::V1_8_7,
+        MinecraftVersion::V1_8_8,
+        MinecraftVersion::V1_8_9,
+        MinecraftVersion::V1_9_4,
+    ];
+
+    pub const OLDEST: MinecraftVersion = MinecraftVersion::V1_4_5;
+    pub const NEWEST: MinecraftVersion = MinecraftVersion::V1_9_4;
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// The release right before this one, the version a downgrade step ending here targets.
+    pub fn previous(self) -> Option<Self> {
+        (self as u8).checked_sub(1).and_then(Self::from_u8)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MinecraftVersion::V1_4_5 => "1.4.5",
+            MinecraftVersion::V1_4_6 => "1.4.6",
+            MinecraftVersion::V1_4_7 => "1.4.7",
+            MinecraftVersion::V1_5 => "1.5",
+            MinecraftVersion::V1_5_1 => "1.5.1",
+            MinecraftVersion::V1_5_2 => "1.5.2",
+            MinecraftVersion::V1_6 => "1.6",
+            MinecraftVersion::V1_6_1 => "1.6.1",
+            MinecraftVersion::V1_6_2 => "1.6.2",
+            MinecraftVersion::V1_6_3 => "1.6.3",
+            MinecraftVersion::V1_6_4 => "1.6.4",
+            MinecraftVersion::V1_7 => "1.7",
+            MinecraftVersion::V1_7_1 => "1.7.1",
+            MinecraftVersion::V1_7_2 => "1.7.2",
+            MinecraftVersion::V1_7_3 => "1.7.3",
+            MinecraftVersion::V1_7_4 => "1.7.4",
+            MinecraftVersion::V1_7_5 => "1.7.5",
+            MinecraftVersion::V1_7_6 => "1.7.6",
+            MinecraftVersion::V1_7_7 => "1.7.7",
+            MinecraftVersion::V1_7_8 => "1.7.8",
+            MinecraftVersion::V1_7_10 => "1.7.10",
+            MinecraftVersion::V1_8 => "1.8",
+            MinecraftVersion::V1_8_1 => "1.8.1",
+            MinecraftVersion::V1_8_2 => "1.8.2",
+            MinecraftVersion::V1_8_3 => "1.8.3",
+            MinecraftVersion::V1_8_4 => "1.8.4",
+            MinecraftVersion::V1_8_5 => "1.8.5",
+            MinecraftVersion::V1_8_6 => "1.8.6",
+            MinecraftVersion::V1_8_7 => "1.8.7",
+            MinecraftVersion::V1_8_8 => "1.8.8",
+            MinecraftVersion::V1_8_9 => "1.8.9",
+            MinecraftVersion::V1_9_4 => "1.9.4",
+        }
+    }
+}
+
+impl fmt::Display for MinecraftVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl Serialize for MinecraftVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl FromStr for MinecraftVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "1.8.0" is how some launchers spell 1.8
+        let s = s.strip_suffix(".0").unwrap_or(s);
+
+        Self::ALL
+            .into_iter()
+            .find(|version| version.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown minecraft version {s:?}, expected one of {}",
+                    Self::ALL.map(Self::as_str).join(", ")
+                )
+            })
+    }
+}
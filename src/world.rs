@@ -0,0 +1,364 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use color_eyre::eyre::Context;
+use tracing::{error, info, warn};
+
+use crate::archive::{FileArchive, ARCHIVE_DIR, FILES_ARCHIVE};
+use crate::fixer::{FixContext, FixerRegistry};
+use crate::region::{ChunkErrorPolicy, RegionStats};
+use crate::selection::{Outside, RegionSelection, Selection};
+use crate::{level_dat, player, region};
+
+/// How a world is converted, apart from the fixers.
+#[derive(Debug, Clone)]
+pub struct WorldOptions {
+    /// Quarantined chunks go to `<dir>/<region path>/c.<x>.<z>.nbt`.
+    pub chunk_errors: ChunkErrorPolicy,
+    /// How many region files are converted at once.
+    pub jobs: usize,
+    /// Whether to keep what the conversion removes or rewrites in an [`ARCHIVE_DIR`].
+    pub archive: bool,
+    /// Which chunks to convert, the rest are skipped or copied as they are.
+    pub selection: Selection,
+}
+
+struct RegionJob {
+    relative_path: PathBuf,
+    input_path: PathBuf,
+    output_path: PathBuf,
+    selection: RegionSelection,
+    chunk_errors: ChunkErrorPolicy,
+    archive_path: Option<PathBuf>,
+}
+
+/// Mirrors a whole world folder, converting every file there's a converter for and copying
+/// everything else as is.
+pub fn convert_world(
+    input_world_path: &Path,
+    output_world_path: &Path,
+    options: &WorldOptions,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<()> {
+    fs::create_dir_all(output_world_path)
+        .wrap_err_with(|| format!("Failed to create output directory at {output_world_path:?}."))?;
+
+    let mut file_archive = options.archive.then(FileArchive::default);
+
+    for file_name in ["level.dat", "level.dat_old"] {
+        let input_path = input_world_path.join(file_name);
+        if input_path.exists() {
+            level_dat::convert_level_dat(
+                &input_path,
+                &output_world_path.join(file_name),
+                file_archive.as_mut(),
+                registry,
+                ctx,
+            )?;
+        } else if file_name == "level.dat" {
+            warn!("No level.dat in the input world, continuing...");
+        }
+    }
+
+    player::convert_players(
+        input_world_path,
+        output_world_path,
+        file_archive.as_mut(),
+        registry,
+        ctx,
+    )?;
+
+    if let Some(file_archive) = file_archive {
+        if !file_archive.is_empty() {
+            file_archive.write(&output_world_path.join(ARCHIVE_DIR).join(FILES_ARCHIVE))?;
+        }
+    }
+
+    let mut passed_through = vec![];
+    let mut region_jobs = vec![];
+    let mut outside_regions = 0;
+
+    for relative_path in walk_files(input_world_path)? {
+        let input_path = input_world_path.join(&relative_path);
+        let output_path = output_world_path.join(&relative_path);
+
+        match classify(&relative_path) {
+            // already done above
+            FileKind::LevelDat | FileKind::Player => {}
+
+            FileKind::Region => {
+                let Some(selection) = options.selection.region(&relative_path) else {
+                    outside_regions += 1;
+                    if options.selection.outside == Outside::Copy {
+                        fs::create_dir_all(output_path.parent().unwrap())?;
+                        fs::copy(&input_path, &output_path).wrap_err_with(|| {
+                            format!("Failed to copy {input_path:?} to {output_path:?}.")
+                        })?;
+                    }
+                    continue;
+                };
+
+                fs::create_dir_all(output_path.parent().unwrap()).wrap_err_with(|| {
+                    format!(
+                        "Failed to create output directory at {:?}.",
+                        output_path.parent().unwrap()
+                    )
+                })?;
+
+                let chunk_errors = match &options.chunk_errors {
+                    ChunkErrorPolicy::Quarantine(dir) => {
+                        ChunkErrorPolicy::Quarantine(dir.join(relative_path.with_extension("")))
+                    }
+                    chunk_errors => chunk_errors.clone(),
+                };
+
+                let archive_path = options.archive.then(|| {
+                    output_world_path
+                        .join(ARCHIVE_DIR)
+                        .join(relative_path.with_extension("dat"))
+                });
+
+                region_jobs.push(RegionJob {
+                    archive_path,
+                    relative_path,
+                    input_path,
+                    output_path,
+                    selection,
+                    chunk_errors,
+                });
+            }
+
+            FileKind::Other => {
+                fs::create_dir_all(output_path.parent().unwrap())?;
+                fs::copy(&input_path, &output_path).wrap_err_with(|| {
+                    format!("Failed to copy {input_path:?} to {output_path:?}.")
+                })?;
+
+                passed_through.push(relative_path);
+            }
+        }
+    }
+
+    let stats = convert_regions(region_jobs, options.jobs, registry, ctx)?;
+    info!(
+        "Converted {} regions with {} chunks, {} of which failed",
+        stats.regions, stats.chunks, stats.failed_chunks
+    );
+    if stats.copied_chunks > 0 {
+        error!(
+            "{} chunks that failed to convert were copied UNCONVERTED and can crash {}! Convert \
+             again with --chunk-errors drop or quarantine:<dir> to leave them out.",
+            stats.copied_chunks, ctx.target
+        );
+    }
+    if outside_regions > 0 || stats.outside_chunks > 0 {
+        let verb = match options.selection.outside {
+            Outside::Skip => "Skipped",
+            Outside::Copy => "Copied without converting",
+        };
+        info!(
+            "{verb} {outside_regions} regions outside the selection and {} chunks outside it in the converted ones",
+            stats.outside_chunks
+        );
+    }
+
+    if !passed_through.is_empty() {
+        warn!(
+            "Copied {} files without converting them, check they are readable by {}:",
+            passed_through.len(),
+            ctx.target
+        );
+        for relative_path in &passed_through {
+            warn!("  {relative_path:?}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts regions on `jobs` threads. Each thread works on one region at a time with its own
+/// copy of `ctx`, whose counts are merged back at the end.
+fn convert_regions(
+    region_jobs: Vec<RegionJob>,
+    jobs: usize,
+    registry: &FixerRegistry,
+    ctx: &mut FixContext,
+) -> color_eyre::Result<RegionStats> {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new(vec![]);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, region_jobs.len().max(1)) {
+            let mut worker_ctx = ctx.fork();
+            let (region_jobs, next, failed, results) = (&region_jobs, &next, &failed, &results);
+
+            scope.spawn(move || {
+                let mut stats = RegionStats::default();
+
+                let result = loop {
+                    if failed.load(Ordering::Relaxed) {
+                        break Ok(());
+                    }
+
+                    let Some(job) = region_jobs.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                        break Ok(());
+                    };
+
+                    info!("Converting region {:?}", job.relative_path);
+                    match region::convert_region_file(
+                        &job.input_path,
+                        &job.output_path,
+                        job.selection,
+                        &job.chunk_errors,
+                        job.archive_path.as_deref(),
+                        registry,
+                        &mut worker_ctx,
+                    ) {
+                        Ok(region_stats) => stats.merge(region_stats),
+                        Err(err) => {
+                            failed.store(true, Ordering::Relaxed);
+                            break Err(err.wrap_err(format!(
+                                "Failed to convert region {:?}.",
+                                job.relative_path
+                            )));
+                        }
+                    }
+                };
+
+                results.lock().unwrap().push((result, stats, worker_ctx));
+            });
+        }
+    });
+
+    let mut stats = RegionStats::default();
+    let mut first_error = None;
+    for (result, worker_stats, worker_ctx) in results.into_inner().unwrap() {
+        stats.merge(worker_stats);
+        ctx.merge(worker_ctx);
+
+        if let Err(err) = result {
+            first_error.get_or_insert(err);
+        }
+    }
+
+    match first_error {
+        Some(err) => Err(err),
+        None => Ok(stats),
+    }
+}
+
+/// A dimension of a world, by the folder its regions are in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+    End,
+    /// `DIM<n>`, from mods.
+    Other(i32),
+}
+
+impl Dimension {
+    /// The dimension of a region file, from its path relative to the world.
+    pub fn of(relative_path: &Path) -> Self {
+        let dir = relative_path
+            .parent()
+            .and_then(Path::parent)
+            .and_then(Path::file_name)
+            .map(|dir| dir.to_string_lossy());
+
+        match dir.as_deref().and_then(|dir| dir.strip_prefix("DIM")) {
+            Some("-1") => Self::Nether,
+            Some("1") => Self::End,
+            Some(n) => n.parse().map_or(Self::Overworld, Self::Other),
+            None => Self::Overworld,
+        }
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overworld" => Ok(Self::Overworld),
+            "nether" => Ok(Self::Nether),
+            "end" => Ok(Self::End),
+            _ => match s.strip_prefix("DIM").map(str::parse) {
+                Some(Ok(-1)) => Ok(Self::Nether),
+                Some(Ok(1)) => Ok(Self::End),
+                Some(Ok(n)) => Ok(Self::Other(n)),
+                _ => Err(format!(
+                    "unknown dimension {s:?}, expected overworld, nether, end or DIM<n>"
+                )),
+            },
+        }
+    }
+}
+
+/// Every region file of a world, relative to it, in all dimensions.
+pub fn region_files(world_path: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    Ok(walk_files(world_path)?
+        .into_iter()
+        .filter(|relative_path| matches!(classify(relative_path), FileKind::Region))
+        .collect())
+}
+
+enum FileKind {
+    LevelDat,
+    Player,
+    Region,
+    Other,
+}
+
+fn classify(relative_path: &Path) -> FileKind {
+    let file_name = relative_path.file_name().unwrap_or_default();
+    let extension = relative_path.extension().unwrap_or_default();
+    let parents: Vec<_> = relative_path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+
+    match parents.as_slice() {
+        [] if file_name == "level.dat" || file_name == "level.dat_old" => FileKind::LevelDat,
+        [dir] if (dir == "playerdata" || dir == "players") && extension == "dat" => {
+            FileKind::Player
+        }
+        [dir] if dir == "region" && extension == "mca" => FileKind::Region,
+        [dim, dir] if dim.starts_with("DIM") && dir == "region" && extension == "mca" => {
+            FileKind::Region
+        }
+        _ => FileKind::Other,
+    }
+}
+
+/// Every file under `root`, relative to it, sorted so output and logs are stable.
+pub fn walk_files(root: &Path) -> color_eyre::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![PathBuf::new()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(root.join(&dir))
+            .wrap_err_with(|| format!("Failed to read directory {:?}.", root.join(&dir)))?
+        {
+            let entry = entry?;
+            let relative_path = dir.join(entry.file_name());
+
+            if entry.file_type()?.is_dir() {
+                dirs.push(relative_path);
+            } else {
+                files.push(relative_path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}